@@ -0,0 +1,96 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use nom::IResult;
+use nom_openmetrics::{
+    parser::{bench, prometheus, Error, ExpositionParser, Format},
+    Sample,
+};
+use std::{fmt::Write, hint::black_box};
+
+/// A single sample line parser
+type SampleParser = for<'a> fn(&'a str) -> IResult<&'a str, Sample<'a>, Error<&'a str>>;
+
+/// Build an exposition shaped like a node_exporter scrape
+///
+/// With `escaped` every label value contains an escape, which the fast path does not handle, so
+/// every sample is parsed with the complete grammar instead.
+fn exposition(families: usize, samples: usize, escaped: bool) -> String {
+    let mut exposition = String::new();
+    let escape = if escaped { "\\\\" } else { "" };
+
+    for family in 0..families {
+        let name = format!("node_example_{family}_seconds_total");
+
+        writeln!(
+            exposition,
+            "# HELP {name} Seconds spent in example {family}"
+        )
+        .unwrap();
+        writeln!(exposition, "# TYPE {name} counter").unwrap();
+
+        for sample in 0..samples {
+            writeln!(
+                exposition,
+                "{name}{{cpu=\"{sample}\",mode=\"user{escape}\",instance=\"node-{family}.example:9100\"}} {}.{sample}",
+                family * 1000 + sample
+            )
+            .unwrap();
+        }
+    }
+
+    exposition
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("prometheus");
+
+    for (name, escaped) in [("fast path", false), ("complete grammar", true)] {
+        let input = exposition(100, 50, escaped);
+
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_function(name, |b| b.iter(|| prometheus(black_box(&input)).unwrap()));
+    }
+
+    group.finish();
+
+    let input = exposition(100, 50, false);
+    let lines: Vec<_> = input
+        .split_inclusive('\n')
+        .filter(|line| !line.starts_with('#'))
+        .collect();
+    let mut group = c.benchmark_group("sample lines");
+
+    group.throughput(Throughput::Elements(lines.len() as u64));
+
+    for (name, sample) in [
+        ("fast path", bench::sample as SampleParser),
+        ("complete grammar", bench::sample_grammar),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                for line in &lines {
+                    sample(black_box(line)).unwrap();
+                }
+            })
+        });
+    }
+
+    group.finish();
+
+    let mut group = c.benchmark_group("exposition parser");
+
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("parse and recycle", |b| {
+        let mut parser = ExpositionParser::new(Format::Prometheus);
+
+        b.iter(|| {
+            let (_, exposition) = parser.parse(black_box(&input)).unwrap();
+
+            parser.recycle(exposition);
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
@@ -0,0 +1,240 @@
+use crate::{Family, Labels, Sample};
+use std::collections::{HashMap, HashSet};
+
+/// An aggregation operator like PromQL `sum` or `max`
+#[derive(Clone, Copy, Debug, Eq, PartialEq, strum::Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum Operator {
+    /// Sum of values
+    Sum,
+    /// Smallest value
+    Min,
+    /// Largest value
+    Max,
+    /// Average of values
+    Avg,
+    /// Number of samples
+    Count,
+}
+
+/// The labels kept by an [`Aggregation`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Grouping {
+    /// Keep only these labels, like PromQL `by (...)`
+    By(HashSet<String>),
+    /// Keep all labels except these, like PromQL `without (...)`
+    Without(HashSet<String>),
+}
+
+impl Grouping {
+    /// Create a `Grouping` keeping only `names`
+    pub fn by<I, S>(names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self::By(names.into_iter().map(Into::into).collect())
+    }
+
+    /// Create a `Grouping` keeping all labels except `names`
+    pub fn without<I, S>(names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self::Without(names.into_iter().map(Into::into).collect())
+    }
+
+    /// Returns `true` if the label named `name` is kept
+    pub fn keeps(&self, name: &str) -> bool {
+        match self {
+            Grouping::By(names) => names.contains(name),
+            Grouping::Without(names) => !names.contains(name),
+        }
+    }
+}
+
+/// Aggregates samples like a PromQL aggregation expression such as `sum by (job) (...)`
+///
+/// Unlike PromQL, samples are also grouped by metric name and results keep the metric name.
+///
+/// ```
+/// use nom_openmetrics::{parser::prometheus, Aggregation, Grouping, Operator};
+///
+/// let input = "requests_total{pod=\"a\",code=\"200\"} 3\nrequests_total{pod=\"b\",code=\"200\"} 4\n";
+/// let (_, families) = prometheus(input).unwrap();
+///
+/// let sum = Aggregation::new(Operator::Sum, Grouping::without(["pod"]));
+///
+/// let samples = families[0].aggregate(&sum);
+///
+/// assert_eq!("requests_total{code=\"200\"} 7", samples[0].to_string());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Aggregation {
+    operator: Operator,
+    grouping: Grouping,
+}
+
+impl Aggregation {
+    /// Create an `Aggregation`
+    pub fn new(operator: Operator, grouping: Grouping) -> Self {
+        Self { operator, grouping }
+    }
+
+    /// Aggregate `samples` into one sample per group, in order of the first sample of each group
+    pub fn apply<'s, 'a: 's>(
+        &self,
+        samples: impl IntoIterator<Item = &'s Sample<'a>>,
+    ) -> Vec<Sample<'a>> {
+        let mut groups: Vec<(String, Labels<'a>, Accumulator)> = vec![];
+        let mut index: HashMap<(String, Labels<'a>), usize> = HashMap::new();
+
+        for sample in samples {
+            let mut labels = sample.labels().clone();
+            labels.retain(|label| self.grouping.keeps(&label.name));
+
+            let key = (sample.name().to_string(), labels);
+
+            let i = match index.get(&key) {
+                Some(i) => *i,
+                None => {
+                    groups.push((key.0.clone(), key.1.clone(), Accumulator::default()));
+                    index.insert(key, groups.len() - 1);
+
+                    groups.len() - 1
+                }
+            };
+
+            groups[i].2.add(sample.number());
+        }
+
+        groups
+            .into_iter()
+            .map(|(name, labels, accumulator)| {
+                Sample::with_labels(name, accumulator.value(self.operator), labels)
+            })
+            .collect()
+    }
+
+    /// The [`Grouping`]
+    pub fn grouping(&self) -> &Grouping {
+        &self.grouping
+    }
+
+    /// The [`Operator`]
+    pub fn operator(&self) -> Operator {
+        self.operator
+    }
+}
+
+impl<'a> Family<'a> {
+    /// Aggregate the samples of this family
+    ///
+    /// See [`Aggregation::apply()`]
+    pub fn aggregate(&self, aggregation: &Aggregation) -> Vec<Sample<'a>> {
+        aggregation.apply(&self.samples)
+    }
+}
+
+/// Running values of a group
+#[derive(Debug, Default)]
+struct Accumulator {
+    count: usize,
+    sum: f64,
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+impl Accumulator {
+    fn add(&mut self, value: f64) {
+        self.count += 1;
+        self.sum += value;
+
+        // As in PromQL, NaN is replaced by any other value
+        self.min = match self.min {
+            Some(min) if !(value < min || min.is_nan()) => Some(min),
+            _ => Some(value),
+        };
+
+        self.max = match self.max {
+            Some(max) if !(value > max || max.is_nan()) => Some(max),
+            _ => Some(value),
+        };
+    }
+
+    fn value(&self, operator: Operator) -> f64 {
+        match operator {
+            Operator::Sum => self.sum,
+            Operator::Min => self.min.unwrap_or(f64::NAN),
+            Operator::Max => self.max.unwrap_or(f64::NAN),
+            Operator::Avg => self.sum / self.count as f64,
+            Operator::Count => self.count as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parser::prometheus, test::parse};
+    use rstest::rstest;
+
+    const INPUT: &str = "# TYPE requests counter
+requests_total{job=\"api\",pod=\"a\",code=\"200\"} 10
+requests_total{job=\"api\",pod=\"b\",code=\"200\"} 30
+requests_total{job=\"api\",pod=\"a\",code=\"500\"} 2
+requests_total{job=\"web\",pod=\"c\",code=\"200\"} 5
+";
+
+    #[rstest]
+    #[case(Operator::Sum, Grouping::by(["job"]), &["requests_total{job=\"api\"} 42", "requests_total{job=\"web\"} 5"])]
+    #[case(Operator::Min, Grouping::by(["job"]), &["requests_total{job=\"api\"} 2", "requests_total{job=\"web\"} 5"])]
+    #[case(Operator::Max, Grouping::by(["code"]), &["requests_total{code=\"200\"} 30", "requests_total{code=\"500\"} 2"])]
+    #[case(Operator::Avg, Grouping::by(["job"]), &["requests_total{job=\"api\"} 14", "requests_total{job=\"web\"} 5"])]
+    #[case(Operator::Count, Grouping::by(Vec::<String>::new()), &["requests_total 4"])]
+    #[case(Operator::Sum, Grouping::without(["pod"]), &[
+        "requests_total{job=\"api\",code=\"200\"} 40",
+        "requests_total{job=\"api\",code=\"500\"} 2",
+        "requests_total{job=\"web\",code=\"200\"} 5",
+    ])]
+    fn apply(#[case] operator: Operator, #[case] grouping: Grouping, #[case] expected: &[&str]) {
+        let (_, families) = parse(prometheus, INPUT);
+
+        let samples = families[0].aggregate(&Aggregation::new(operator, grouping));
+
+        let samples: Vec<_> = samples.iter().map(ToString::to_string).collect();
+
+        assert_eq!(expected, samples, "{operator}");
+    }
+
+    #[test]
+    fn apply_groups_by_name() {
+        let samples = [
+            Sample::new("latency_count", 3.0).add_label("pod", "a"),
+            Sample::new("latency_sum", 1.5).add_label("pod", "a"),
+            Sample::new("latency_count", 4.0).add_label("pod", "b"),
+        ];
+
+        let sums = Aggregation::new(Operator::Sum, Grouping::without(["pod"])).apply(&samples);
+
+        assert_eq!(
+            vec![
+                Sample::new("latency_count", 7.0),
+                Sample::new("latency_sum", 1.5)
+            ],
+            sums
+        );
+    }
+
+    #[test]
+    fn apply_nan() {
+        let samples = [Sample::new("up", f64::NAN), Sample::new("up", 1.0)];
+
+        let min = Aggregation::new(Operator::Min, Grouping::by(["job"])).apply(&samples);
+        let max = Aggregation::new(Operator::Max, Grouping::by(["job"])).apply(&samples);
+
+        assert_eq!(1.0, min[0].number());
+        assert_eq!(1.0, max[0].number());
+    }
+}
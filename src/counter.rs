@@ -0,0 +1,113 @@
+use crate::{Family, Fingerprint, Labels};
+use std::collections::HashMap;
+
+/// The value and `_created` samples of one counter series
+///
+/// Create these with [`Family::counters()`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Counter<'a> {
+    /// The name of the value sample, such as `requests_total`
+    pub name: String,
+    /// Labels of the series
+    pub labels: Labels<'a>,
+    /// The counter value
+    pub value: f64,
+    /// The `_created` or `st@` start time in seconds since the Unix epoch
+    pub created: Option<f64>,
+}
+
+impl Counter<'_> {
+    /// The [`Fingerprint`] of the series
+    pub fn fingerprint(&self) -> Fingerprint {
+        Fingerprint::new(&self.name, &self.labels)
+    }
+}
+
+impl<'a> Family<'a> {
+    /// Group the samples of a counter family into [`Counter`]s by labels
+    ///
+    /// The `_created` sample of a series is its creation time rather than a counter.  A series
+    /// with only a `_created` sample is ignored.  Without a `_created` sample the OpenMetrics 2.0
+    /// start timestamp of the value sample is used.
+    pub fn counters(&self) -> Vec<Counter<'a>> {
+        let name = self.name().unwrap_or_default();
+        let created_name = format!("{name}_created");
+
+        let created: HashMap<&Labels<'a>, f64> = self
+            .samples
+            .iter()
+            .filter(|sample| sample.name() == created_name)
+            .map(|sample| (sample.labels(), sample.number()))
+            .collect();
+
+        self.samples
+            .iter()
+            .filter(|sample| sample.name() != created_name)
+            .map(|sample| Counter {
+                name: sample.name().into(),
+                labels: sample.labels().clone(),
+                value: sample.number(),
+                created: created
+                    .get(sample.labels())
+                    .copied()
+                    .or(sample.start_timestamp()),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        parser::{openmetrics, openmetrics2},
+        test::parse,
+    };
+
+    #[test]
+    fn counters() {
+        let input = "# TYPE requests counter
+requests_total{code=\"200\"} 1027
+requests_created{code=\"200\"} 1700000000
+requests_total{code=\"500\"} 3
+# EOF
+";
+        let (_, families) = parse(openmetrics, input);
+
+        assert_eq!(
+            vec![
+                Counter {
+                    name: "requests_total".into(),
+                    labels: Labels::new().with("code", "200"),
+                    value: 1027.0,
+                    created: Some(1700000000.0),
+                },
+                Counter {
+                    name: "requests_total".into(),
+                    labels: Labels::new().with("code", "500"),
+                    value: 3.0,
+                    created: None,
+                },
+            ],
+            families[0].counters()
+        );
+    }
+
+    #[test]
+    fn counters_start_timestamp() {
+        let input = "# TYPE requests counter
+requests_total{code=\"200\"} 1027 st@1700000000
+requests_total{code=\"500\"} 3
+# EOF
+";
+        let (_, families) = parse(openmetrics2, input);
+
+        let created: Vec<_> = families[0]
+            .counters()
+            .iter()
+            .map(|counter| counter.created)
+            .collect();
+
+        assert_eq!(vec![Some(1700000000.0), None], created);
+    }
+}
@@ -0,0 +1,277 @@
+use crate::{parser::metric_name::is_metric_name, Exposition, Labels};
+use std::{borrow::Cow, fmt::Write};
+
+/// A scheme for escaping UTF-8 metric and label names into legacy names
+///
+/// These are the values of the `escaping` parameter in Prometheus content negotiation, such as
+/// `application/openmetrics-text; version=1.0.0; escaping=values`.  Legacy names match
+/// `[a-zA-Z_:][a-zA-Z0-9_:]*` and are left alone by [`Underscores`](Self::Underscores) and
+/// [`Values`](Self::Values).
+///
+/// ```
+/// use nom_openmetrics::Escaping;
+///
+/// assert_eq!("U__my_2e_metric", Escaping::Values.escape("my.metric"));
+/// assert_eq!("my.metric", Escaping::Values.unescape("U__my_2e_metric"));
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq, strum::Display, strum::EnumString)]
+pub enum Escaping {
+    /// Names are not escaped
+    #[strum(serialize = "allow-utf-8")]
+    AllowUtf8,
+    /// Each invalid character becomes `_`, which cannot be unescaped
+    #[strum(serialize = "underscores")]
+    Underscores,
+    /// `.` becomes `_dot_`, `_` becomes `__`, and other invalid characters become `__`
+    #[strum(serialize = "dots")]
+    Dots,
+    /// Invalid names are prefixed with `U__`, `_` becomes `__`, and invalid characters become
+    /// their code point in hex surrounded by `_`
+    #[strum(serialize = "values")]
+    Values,
+}
+
+impl Escaping {
+    /// The `escaping` parameter of an HTTP `Accept` or `Content-Type` header value
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        parameter(content_type, "escaping")?.parse().ok()
+    }
+
+    /// Escape `name`, borrowing it when it is unchanged
+    pub fn escape(self, name: &str) -> Cow<'_, str> {
+        if name.is_empty() {
+            return Cow::Borrowed(name);
+        }
+
+        match self {
+            Self::AllowUtf8 => Cow::Borrowed(name),
+            Self::Underscores if is_metric_name(name) => Cow::Borrowed(name),
+            Self::Underscores => name
+                .char_indices()
+                .map(|(i, c)| if is_legacy(i, c) { c } else { '_' })
+                .collect(),
+            Self::Dots => {
+                let mut escaped = String::with_capacity(name.len());
+
+                for (i, c) in name.char_indices() {
+                    match c {
+                        '_' => escaped.push_str("__"),
+                        '.' => escaped.push_str("_dot_"),
+                        c if is_legacy(i, c) => escaped.push(c),
+                        _ => escaped.push_str("__"),
+                    }
+                }
+
+                Cow::Owned(escaped)
+            }
+            Self::Values if is_metric_name(name) => Cow::Borrowed(name),
+            Self::Values => {
+                let mut escaped = String::from("U__");
+
+                for (i, c) in name.char_indices() {
+                    match c {
+                        '_' => escaped.push_str("__"),
+                        c if is_legacy(i, c) => escaped.push(c),
+                        c => write!(escaped, "_{:x}_", u32::from(c)).expect("write to String"),
+                    }
+                }
+
+                Cow::Owned(escaped)
+            }
+        }
+    }
+
+    /// Unescape `name`, borrowing it when it is unchanged
+    ///
+    /// Only [`Dots`](Self::Dots) and [`Values`](Self::Values) can be unescaped.  A `U__` name with
+    /// invalid escapes is returned unchanged.
+    pub fn unescape(self, name: &str) -> Cow<'_, str> {
+        match self {
+            Self::AllowUtf8 | Self::Underscores => Cow::Borrowed(name),
+            Self::Dots if !name.contains('_') => Cow::Borrowed(name),
+            Self::Dots => Cow::Owned(name.replace("_dot_", ".").replace("__", "_")),
+            Self::Values => match name.strip_prefix("U__").and_then(unescape_values) {
+                Some(unescaped) => Cow::Owned(unescaped),
+                None => Cow::Borrowed(name),
+            },
+        }
+    }
+}
+
+impl Exposition<'_> {
+    /// Escape the metric and label names of all families with `escaping`
+    ///
+    /// Use this before sending an exposition to a consumer that only accepts legacy names.
+    pub fn escape_names(&mut self, escaping: Escaping) {
+        self.rename(|name| escaping.escape(name));
+    }
+
+    /// Unescape the metric and label names of all families escaped with `escaping`
+    ///
+    /// Use this to recover the original UTF-8 names from an exposition of a target that escaped
+    /// them for older consumers.
+    pub fn unescape_names(&mut self, escaping: Escaping) {
+        self.rename(|name| escaping.unescape(name));
+    }
+
+    /// Replace metric and label names for which `f` returns an owned name
+    fn rename(&mut self, f: impl Fn(&str) -> Cow<'_, str>) {
+        for family in self.families_mut() {
+            for descriptor in &mut family.descriptors {
+                if let Cow::Owned(metric) = f(descriptor.metric()) {
+                    descriptor.set_metric(metric);
+                }
+            }
+
+            for sample in &mut family.samples {
+                if let Cow::Owned(name) = f(sample.name()) {
+                    sample.set_name(name);
+                }
+
+                if !sample
+                    .labels()
+                    .iter()
+                    .any(|label| matches!(f(&label.name), Cow::Owned(_)))
+                {
+                    continue;
+                }
+
+                let mut labels = std::mem::take(sample.labels_mut()).into_vec();
+
+                for label in &mut labels {
+                    if let Cow::Owned(name) = f(&label.name) {
+                        label.name = Cow::Owned(name);
+                    }
+                }
+
+                *sample.labels_mut() = Labels::from(labels);
+            }
+        }
+    }
+}
+
+/// The value of the parameter `name` of an HTTP header value such as `Content-Type`
+pub(crate) fn parameter<'h>(header: &'h str, name: &str) -> Option<&'h str> {
+    header
+        .split(';')
+        .skip(1)
+        .filter_map(|parameter| parameter.split_once('='))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.trim().trim_matches('"'))
+}
+
+/// Returns `true` if `c` at byte offset `i` is valid in a legacy name
+fn is_legacy(i: usize, c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || c == ':' || (c.is_ascii_digit() && i > 0)
+}
+
+/// Decode the `Values` escapes of a name without its `U__` prefix
+fn unescape_values(escaped: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(escaped.len());
+    let mut chars = escaped.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '_' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next()? {
+            '_' => unescaped.push('_'),
+            first => {
+                let mut code = first.to_digit(16)?;
+
+                for digits in 1.. {
+                    match chars.next()? {
+                        '_' => break,
+                        _ if digits >= 6 => return None,
+                        c => code = code * 16 + c.to_digit(16)?,
+                    }
+                }
+
+                unescaped.push(char::from_u32(code)?);
+            }
+        }
+    }
+
+    Some(unescaped)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parser::openmetrics, test::parse};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Escaping::AllowUtf8, "my.metric", "my.metric")]
+    #[case(Escaping::Underscores, "my.metric", "my_metric")]
+    #[case(Escaping::Underscores, "0my.métric", "_my_m_tric")]
+    #[case(Escaping::Dots, "my.metric", "my_dot_metric")]
+    #[case(Escaping::Dots, "my_metric", "my__metric")]
+    #[case(Escaping::Dots, "my.métric", "my_dot_m__tric")]
+    #[case(Escaping::Values, "my_metric", "my_metric")]
+    #[case(Escaping::Values, "my.metric", "U__my_2e_metric")]
+    #[case(Escaping::Values, "my_métric.☃", "U__my__m_e9_tric_2e__2603_")]
+    #[case(Escaping::Values, "0up", "U___30_up")]
+    #[case(Escaping::Values, "", "")]
+    fn escape(#[case] escaping: Escaping, #[case] name: &str, #[case] expected: &str) {
+        assert_eq!(expected, escaping.escape(name));
+    }
+
+    #[rstest]
+    #[case(Escaping::Dots, "my_dot_metric", "my.metric")]
+    #[case(Escaping::Dots, "my__metric", "my_metric")]
+    #[case(Escaping::Values, "U__my__m_e9_tric_2e__2603_", "my_métric.☃")]
+    #[case(Escaping::Values, "U___30_up", "0up")]
+    #[case(Escaping::Values, "my_metric", "my_metric")]
+    #[case(Escaping::Values, "U__my_2e", "U__my_2e")]
+    #[case(Escaping::Values, "U__my_zz_", "U__my_zz_")]
+    #[case(Escaping::Values, "U__my_1234567_", "U__my_1234567_")]
+    #[case(Escaping::Values, "U__my_d800_", "U__my_d800_")]
+    #[case(Escaping::Underscores, "my_metric", "my_metric")]
+    fn unescape(#[case] escaping: Escaping, #[case] name: &str, #[case] expected: &str) {
+        assert_eq!(expected, escaping.unescape(name));
+    }
+
+    #[rstest]
+    #[case(
+        "application/openmetrics-text; version=1.0.0; escaping=values",
+        Some(Escaping::Values)
+    )]
+    #[case(
+        "text/plain; version=0.0.4; escaping=allow-utf-8",
+        Some(Escaping::AllowUtf8)
+    )]
+    #[case("text/plain; Escaping=\"dots\"", Some(Escaping::Dots))]
+    #[case("text/plain; escaping=unknown", None)]
+    #[case("text/plain; version=0.0.4", None)]
+    fn from_content_type(#[case] content_type: &str, #[case] expected: Option<Escaping>) {
+        assert_eq!(expected, Escaping::from_content_type(content_type));
+    }
+
+    #[test]
+    fn unescape_names() {
+        let input = "# TYPE U__http_2e_requests counter
+U__http_2e_requests__total{U__http_2e_method=\"GET\",code=\"200\"} 1027
+# EOF
+";
+        let (_, families) = parse(openmetrics, input);
+        let mut exposition = Exposition::from(families);
+
+        exposition.unescape_names(Escaping::Values);
+
+        let family = exposition
+            .family("http.requests")
+            .expect("unescaped family");
+
+        assert_eq!(
+            "{\"http.requests_total\",\"http.method\"=\"GET\",code=\"200\"} 1027",
+            family.samples[0].to_string()
+        );
+
+        exposition.escape_names(Escaping::Values);
+
+        assert!(exposition.family("U__http_2e_requests").is_some());
+    }
+}
@@ -0,0 +1,239 @@
+use crate::{metric_descriptor::SUFFIXES, Family, Sample, Selector};
+use std::{collections::HashMap, sync::OnceLock};
+
+/// A parsed exposition
+///
+/// Lookups by name use an index that is built on first use.
+#[derive(Debug, Default)]
+pub struct Exposition<'a> {
+    families: Vec<Family<'a>>,
+    index: OnceLock<Index>,
+}
+
+impl<'a> Exposition<'a> {
+    /// Create an `Exposition`
+    pub fn new(families: Vec<Family<'a>>) -> Self {
+        Self {
+            families,
+            index: OnceLock::new(),
+        }
+    }
+
+    /// The [`Family`]s of the exposition in exposition order
+    pub fn families(&self) -> &[Family<'a>] {
+        &self.families
+    }
+
+    /// The [`Family`] named `name`
+    ///
+    /// See [`Family::name()`]
+    pub fn family(&self, name: &str) -> Option<&Family<'a>> {
+        self.families.get(self.family_position(name)?)
+    }
+
+    /// The [`Family`] containing samples named `name`
+    ///
+    /// If no sample is named `name` a suffix such as `_bucket` or `_total` is removed and the
+    /// family with the remaining name is returned when the suffix is valid for its type.  Families
+    /// without a TYPE have no suffixes.
+    pub fn family_for_sample(&self, name: &str) -> Option<&Family<'a>> {
+        self.families.get(self.sample_family_position(name)?)
+    }
+
+    /// Consume an `Exposition` returning its [`Family`]s
+    pub fn into_families(self) -> Vec<Family<'a>> {
+        self.families
+    }
+
+    /// Returns `true` if the exposition has no families
+    pub fn is_empty(&self) -> bool {
+        self.families.is_empty()
+    }
+
+    /// The number of [`Family`]s
+    pub fn len(&self) -> usize {
+        self.families.len()
+    }
+
+    /// The number of [`Sample`]s in all families
+    pub fn sample_count(&self) -> usize {
+        self.families
+            .iter()
+            .map(|family| family.samples.len())
+            .sum()
+    }
+
+    /// All [`Sample`]s in exposition order
+    pub fn samples(&self) -> impl Iterator<Item = &Sample<'a>> {
+        self.families.iter().flat_map(|family| &family.samples)
+    }
+
+    /// The [`Sample`]s matching `selector` in exposition order
+    pub fn select<'s>(&'s self, selector: &'s Selector) -> impl Iterator<Item = &'s Sample<'a>> {
+        self.samples().filter(|sample| selector.matches(sample))
+    }
+
+    /// The [`Family`]s of the exposition for reuse by a parser
+    pub(crate) fn families_mut(&mut self) -> &mut Vec<Family<'a>> {
+        self.index = OnceLock::new();
+
+        &mut self.families
+    }
+
+    /// The position of the family named `name`
+    pub(crate) fn family_position(&self, name: &str) -> Option<usize> {
+        self.index().families.get(name).copied()
+    }
+
+    /// The position of the family containing samples named `name`
+    pub(crate) fn sample_family_position(&self, name: &str) -> Option<usize> {
+        let index = self.index();
+
+        index.samples.get(name).copied().or_else(|| {
+            SUFFIXES.iter().find_map(|suffix| {
+                let position = *index.families.get(name.strip_suffix(suffix)?)?;
+                let metric_type = self.families[position].metric_type()?;
+
+                metric_type.suffixes().contains(suffix).then_some(position)
+            })
+        })
+    }
+
+    fn index(&self) -> &Index {
+        self.index.get_or_init(|| Index::new(&self.families))
+    }
+}
+
+impl<'a> From<Vec<Family<'a>>> for Exposition<'a> {
+    fn from(families: Vec<Family<'a>>) -> Self {
+        Self::new(families)
+    }
+}
+
+impl PartialEq for Exposition<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.families == other.families
+    }
+}
+
+/// Positions of families by family name and by sample name
+///
+/// When names repeat the first family wins.
+#[derive(Debug, Default)]
+struct Index {
+    families: HashMap<String, usize>,
+    samples: HashMap<String, usize>,
+}
+
+impl Index {
+    fn new(families: &[Family<'_>]) -> Self {
+        let mut index = Self::default();
+
+        for (i, family) in families.iter().enumerate() {
+            if let Some(name) = family.name() {
+                index.families.entry(name.into()).or_insert(i);
+            }
+
+            for sample in &family.samples {
+                if !index.samples.contains_key(sample.name()) {
+                    index.samples.insert(sample.name().into(), i);
+                }
+            }
+        }
+
+        index
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parser::prometheus, test::parse};
+    use rstest::rstest;
+
+    const INPUT: &str = "# TYPE up gauge\nup{job=\"prometheus\"} 1\nup{job=\"grafana\"} 0\n# TYPE requests counter\nrequests_total 1027\n# TYPE latency summary\nlatency{quantile=\"0.5\"} 0.25\nlatency_count 3\nlatency_sum 1.5\n";
+
+    fn exposition() -> Exposition<'static> {
+        let (_, families) = parse(prometheus, INPUT);
+
+        Exposition::from(families)
+    }
+
+    #[rstest]
+    #[case("up", Some("up"))]
+    #[case("requests", Some("requests"))]
+    #[case("requests_total", None)]
+    #[case("missing", None)]
+    fn family(#[case] name: &str, #[case] expected: Option<&str>) {
+        let exposition = exposition();
+
+        assert_eq!(
+            expected,
+            exposition.family(name).and_then(Family::name),
+            "name: {name}"
+        );
+    }
+
+    #[rstest]
+    #[case("up", Some("up"))]
+    #[case("requests_total", Some("requests"))]
+    #[case("requests_created", Some("requests"))]
+    #[case("latency", Some("latency"))]
+    #[case("latency_count", Some("latency"))]
+    #[case("latency_created", Some("latency"))]
+    #[case("latency_bucket", None)]
+    #[case("requests_count", None)]
+    #[case("up_info", None)]
+    #[case("missing_total", None)]
+    fn family_for_sample(#[case] name: &str, #[case] expected: Option<&str>) {
+        let exposition = exposition();
+
+        assert_eq!(
+            expected,
+            exposition.family_for_sample(name).and_then(Family::name),
+            "name: {name}"
+        );
+    }
+
+    #[test]
+    fn counts() {
+        let exposition = exposition();
+
+        assert_eq!(3, exposition.len());
+        assert_eq!(6, exposition.sample_count());
+        assert_eq!(
+            vec![
+                "up",
+                "up",
+                "requests_total",
+                "latency",
+                "latency_count",
+                "latency_sum"
+            ],
+            exposition.samples().map(Sample::name).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn select() {
+        let exposition = exposition();
+        let (_, selector) = parse(crate::parser::selector, "up{job!=\"grafana\"}");
+
+        let selected: Vec<_> = exposition.select(&selector).collect();
+
+        assert_eq!(1, selected.len());
+        assert_eq!(Some("prometheus"), selected[0].labels().get("job"));
+    }
+
+    #[test]
+    fn families_mut() {
+        let mut exposition = exposition();
+
+        assert!(exposition.family("up").is_some());
+
+        exposition.families_mut().remove(0);
+
+        assert!(exposition.family("up").is_none());
+        assert!(exposition.family("requests").is_some());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{MetricDescriptor, Sample};
+use crate::{MetricDescriptor, MetricType, Sample};
 
 /// A metric's [descriptor](MetricDescriptor)s and its set of [`Sample`]s
 #[derive(Debug, PartialEq)]
@@ -15,4 +15,77 @@ impl<'a> Family<'a> {
             samples,
         }
     }
+
+    /// The HELP text
+    pub fn help(&self) -> Option<&str> {
+        self.descriptors
+            .iter()
+            .find_map(|descriptor| match descriptor {
+                MetricDescriptor::Help { help, .. } => Some(help.as_str()),
+                _ => None,
+            })
+    }
+
+    /// The [`MetricType`]
+    pub fn metric_type(&self) -> Option<&MetricType<'a>> {
+        self.descriptors
+            .iter()
+            .find_map(|descriptor| match descriptor {
+                MetricDescriptor::Type { r#type, .. } => Some(r#type),
+                _ => None,
+            })
+    }
+
+    /// The metric name
+    ///
+    /// This is the metric name of the descriptors, or the name of the first sample if there are
+    /// no descriptors.
+    pub fn name(&self) -> Option<&str> {
+        self.descriptors
+            .first()
+            .map(MetricDescriptor::metric)
+            .or_else(|| self.samples.first().map(Sample::name))
+    }
+
+    /// The unit
+    pub fn unit(&self) -> Option<&'a str> {
+        self.descriptors
+            .iter()
+            .find_map(|descriptor| match descriptor {
+                MetricDescriptor::Unit { unit, .. } => Some(*unit),
+                _ => None,
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn accessors() {
+        let family = Family::new(
+            vec![
+                MetricDescriptor::help("requests", "Requests served".into()),
+                MetricDescriptor::r#type("requests", MetricType::Counter),
+                MetricDescriptor::unit("requests", "requests"),
+            ],
+            vec![Sample::new("requests_total", 1027.0)],
+        );
+
+        assert_eq!(Some("requests"), family.name());
+        assert_eq!(Some("Requests served"), family.help());
+        assert_eq!(Some(&MetricType::Counter), family.metric_type());
+        assert_eq!(Some("requests"), family.unit());
+    }
+
+    #[test]
+    fn accessors_without_descriptors() {
+        let family = Family::new(vec![], vec![Sample::new("up", 1.0)]);
+
+        assert_eq!(Some("up"), family.name());
+        assert_eq!(None, family.help());
+        assert_eq!(None, family.metric_type());
+        assert_eq!(None, family.unit());
+    }
 }
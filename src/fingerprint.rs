@@ -0,0 +1,120 @@
+use crate::{Labels, Sample};
+use std::fmt;
+use xxhash_rust::xxh64::Xxh64;
+
+/// Separates names and values in the hashed bytes, as in Prometheus
+const SEPARATOR: u8 = 0xff;
+
+/// The label name Prometheus uses for the metric name
+const METRIC_NAME: &str = "__name__";
+
+/// A series identity independent of label order
+///
+/// The value is identical to Prometheus' `labels.Labels.Hash()` for the same labels, including the
+/// metric name as the `__name__` label.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Fingerprint(pub u64);
+
+impl Fingerprint {
+    /// Compute the `Fingerprint` of a series named `name` with `labels`
+    ///
+    /// An empty `name` hashes only `labels`.
+    pub fn new(name: &str, labels: &Labels<'_>) -> Self {
+        let name = (!name.is_empty()).then_some((METRIC_NAME, name));
+
+        hash(
+            name.into_iter()
+                .chain(labels.iter().map(|l| (l.name.as_ref(), l.value.as_str()))),
+        )
+    }
+
+    /// The fingerprint value
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl From<&Labels<'_>> for Fingerprint {
+    fn from(labels: &Labels<'_>) -> Self {
+        Self::new("", labels)
+    }
+}
+
+impl From<&Sample<'_>> for Fingerprint {
+    fn from(sample: &Sample<'_>) -> Self {
+        Self::new(sample.name(), sample.labels())
+    }
+}
+
+/// Hash name and value pairs sorted by name with xxHash64
+fn hash<'a>(labels: impl Iterator<Item = (&'a str, &'a str)>) -> Fingerprint {
+    let mut labels: Vec<_> = labels.collect();
+
+    labels.sort_unstable();
+
+    let mut hasher = Xxh64::new(0);
+
+    for (name, value) in labels {
+        hasher.update(name.as_bytes());
+        hasher.update(&[SEPARATOR]);
+        hasher.update(value.as_bytes());
+        hasher.update(&[SEPARATOR]);
+    }
+
+    Fingerprint(hasher.digest())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use xxhash_rust::xxh64::xxh64;
+
+    #[test]
+    fn empty() {
+        assert_eq!(
+            Fingerprint(0xef46db3751d8e999),
+            Fingerprint::from(&Labels::new())
+        );
+    }
+
+    #[test]
+    fn prometheus_layout() {
+        let sample = Sample::new("up", 1.0)
+            .add_label("job", "prometheus")
+            .add_label("instance", "localhost:9090");
+
+        let expected = xxh64(
+            b"__name__\xffup\xffinstance\xfflocalhost:9090\xffjob\xffprometheus\xff",
+            0,
+        );
+
+        assert_eq!(Fingerprint(expected), Fingerprint::from(&sample));
+    }
+
+    #[test]
+    fn label_order() {
+        let a = Sample::new("up", 1.0)
+            .add_label("a", "1")
+            .add_label("b", "2");
+        let b = Sample::new("up", 0.0)
+            .add_label("b", "2")
+            .add_label("a", "1");
+        let c = Sample::new("up", 1.0)
+            .add_label("a", "2")
+            .add_label("b", "1");
+
+        assert_eq!(a.fingerprint(), b.fingerprint());
+        assert_ne!(a.fingerprint(), c.fingerprint());
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("00000000000000ff", Fingerprint(0xff).to_string());
+    }
+}
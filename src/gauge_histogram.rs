@@ -0,0 +1,183 @@
+use crate::{
+    histogram::{bucket_quantile, sorted},
+    Bucket, Family, Labels, MetricType, ValidationError,
+};
+
+/// The `_bucket`, `_gcount`, and `_gsum` samples of one gauge histogram series
+///
+/// Create these with [`Family::gauge_histograms()`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct GaugeHistogram<'a> {
+    /// Labels of the series without `le`
+    pub labels: Labels<'a>,
+    /// Buckets sorted by upper bound
+    pub buckets: Vec<Bucket>,
+    /// The `_gcount` value
+    pub gcount: f64,
+    /// The `_gsum` value
+    pub gsum: Option<f64>,
+}
+
+impl GaugeHistogram<'_> {
+    /// Estimate the `q` quantile of the current distribution
+    ///
+    /// See [`bucket_quantile()`]
+    pub fn quantile(&self, q: f64) -> f64 {
+        bucket_quantile(q, &self.buckets)
+    }
+}
+
+impl<'a> Family<'a> {
+    /// Group the samples of a gauge histogram family into [`GaugeHistogram`]s by labels
+    ///
+    /// The family must have the type gaugehistogram.  Samples are grouped by the `_bucket`,
+    /// `_gcount`, and `_gsum` suffixes of the family name.  Each bucket must have a valid `le`
+    /// label, each series must have a `+Inf` bucket and a `_gcount`, bucket counts must not
+    /// decrease as the upper bound increases, and `_gcount` must equal the `+Inf` bucket.
+    pub fn gauge_histograms(&self) -> Result<Vec<GaugeHistogram<'a>>, ValidationError> {
+        self.expect_type(MetricType::Gaugehistogram)?;
+
+        let name = self.name().unwrap_or_default();
+
+        let invalid_bucket = self.samples.iter().find(|sample| {
+            let le = sample.labels().get("le");
+
+            sample.name().strip_prefix(name) == Some("_bucket")
+                && le.and_then(|le| le.parse::<f64>().ok()).is_none()
+        });
+
+        if let Some(sample) = invalid_bucket {
+            return Err(ValidationError::InvalidBucketBound {
+                sample_line: sample.to_string(),
+            });
+        }
+
+        self.group_histograms("_gcount", "_gsum")
+            .into_iter()
+            .map(|histogram| {
+                let series = format!("{name}{}", histogram.labels);
+                let buckets = sorted(&histogram.buckets);
+
+                if buckets.windows(2).any(|pair| pair[1].count < pair[0].count) {
+                    return Err(ValidationError::NonMonotonicBuckets { series });
+                }
+
+                let Some(inf) = buckets
+                    .last()
+                    .filter(|bucket| bucket.upper_bound == f64::INFINITY)
+                else {
+                    return Err(ValidationError::MissingInfBucket { series });
+                };
+
+                let Some(gcount) = histogram.count else {
+                    return Err(ValidationError::MissingCount { series });
+                };
+
+                if gcount != inf.count {
+                    return Err(ValidationError::CountMismatch {
+                        series,
+                        count: gcount,
+                        bucket: inf.count,
+                    });
+                }
+
+                Ok(GaugeHistogram {
+                    labels: histogram.labels,
+                    buckets,
+                    gcount,
+                    gsum: histogram.sum,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parser::openmetrics, test::parse};
+    use rstest::rstest;
+
+    const INPUT: &str = "# TYPE queue gaugehistogram
+queue_bucket{queue=\"a\",le=\"10\"} 2
+queue_bucket{queue=\"a\",le=\"100\"} 5
+queue_bucket{queue=\"a\",le=\"+Inf\"} 6
+queue_gcount{queue=\"a\"} 6
+queue_gsum{queue=\"a\"} 250
+queue_bucket{queue=\"b\",le=\"10\"} 0
+queue_bucket{queue=\"b\",le=\"+Inf\"} 0
+queue_gcount{queue=\"b\"} 0
+# EOF
+";
+
+    #[test]
+    fn gauge_histograms() {
+        let (_, families) = parse(openmetrics, INPUT);
+
+        let histograms = families[0].gauge_histograms().unwrap();
+
+        assert_eq!(
+            GaugeHistogram {
+                labels: Labels::new().with("queue", "a"),
+                buckets: vec![
+                    Bucket::new(10.0, 2.0),
+                    Bucket::new(100.0, 5.0),
+                    Bucket::new(f64::INFINITY, 6.0),
+                ],
+                gcount: 6.0,
+                gsum: Some(250.0),
+            },
+            histograms[0]
+        );
+        assert_eq!(None, histograms[1].gsum);
+        assert_eq!(10.0, histograms[0].quantile(1.0 / 3.0));
+        assert!(histograms[1].quantile(0.5).is_nan());
+    }
+
+    #[rstest]
+    #[case(
+        "gaugehistogram",
+        "queue_bucket{le=\"10\"} 3\nqueue_bucket{le=\"+Inf\"} 2\n",
+        ValidationError::NonMonotonicBuckets { series: "queue".into() }
+    )]
+    #[case(
+        "gaugehistogram",
+        "queue_bucket{le=\"10\"} 3\nqueue_gcount 3\n",
+        ValidationError::MissingInfBucket { series: "queue".into() }
+    )]
+    #[case(
+        "gaugehistogram",
+        "queue_bucket{le=\"+Inf\"} 3\n",
+        ValidationError::MissingCount { series: "queue".into() }
+    )]
+    #[case(
+        "gaugehistogram",
+        "queue_bucket 3\nqueue_gcount 3\n",
+        ValidationError::InvalidBucketBound { sample_line: "queue_bucket 3".into() }
+    )]
+    #[case(
+        "gaugehistogram",
+        "queue_bucket{le=\"ten\"} 3\nqueue_bucket{le=\"+Inf\"} 3\nqueue_gcount 3\n",
+        ValidationError::InvalidBucketBound { sample_line: "queue_bucket{le=\"ten\"} 3".into() }
+    )]
+    #[case(
+        "gaugehistogram",
+        "queue_bucket{q=\"x\",le=\"+Inf\"} 3\nqueue_gcount{q=\"x\"} 4\n",
+        ValidationError::CountMismatch { series: "queue{q=\"x\"}".into(), count: 4.0, bucket: 3.0 }
+    )]
+    #[case(
+        "histogram",
+        "queue_bucket{le=\"+Inf\"} 3\nqueue_count 3\n",
+        ValidationError::WrongType { family: "queue".into(), expected: "gaugehistogram".into() }
+    )]
+    fn gauge_histograms_invalid(
+        #[case] metric_type: &str,
+        #[case] samples: &str,
+        #[case] expected: ValidationError,
+    ) {
+        let input = format!("# TYPE queue {metric_type}\n{samples}# EOF\n");
+        let (_, families) = parse(openmetrics, &input);
+
+        assert_eq!(Err(expected), families[0].gauge_histograms());
+    }
+}
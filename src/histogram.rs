@@ -0,0 +1,643 @@
+use crate::{Family, Labels};
+use std::collections::HashMap;
+
+/// A cumulative histogram bucket
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bucket {
+    /// The `le` label value
+    pub upper_bound: f64,
+    /// The number of observations less than or equal to `upper_bound`
+    pub count: f64,
+}
+
+impl Bucket {
+    /// Create a `Bucket`
+    pub fn new(upper_bound: f64, count: f64) -> Self {
+        Self { upper_bound, count }
+    }
+}
+
+/// The `_bucket`, `_count`, `_sum`, and `_created` samples of one histogram series
+///
+/// Create these with [`Family::histograms()`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Histogram<'a> {
+    /// Labels of the series without `le`
+    pub labels: Labels<'a>,
+    /// Buckets in exposition order
+    pub buckets: Vec<Bucket>,
+    /// The `_count` value
+    pub count: Option<f64>,
+    /// The `_sum` value
+    pub sum: Option<f64>,
+    /// The `_created` or `st@` start time in seconds since the Unix epoch
+    pub created: Option<f64>,
+}
+
+impl<'a> Histogram<'a> {
+    /// Create an empty `Histogram` for the series with `labels`
+    pub fn new(labels: Labels<'a>) -> Self {
+        Self {
+            labels,
+            buckets: vec![],
+            count: None,
+            sum: None,
+            created: None,
+        }
+    }
+
+    /// The observations since `previous`, such as between two scrapes
+    ///
+    /// If the histogram was reset since `previous` all observations are returned.  Buckets are
+    /// sorted by upper bound.  Returns `None` if the bucket boundaries changed.
+    pub fn delta(&self, previous: &Histogram<'_>) -> Option<Histogram<'a>> {
+        let buckets = sorted(&self.buckets);
+        let before = sorted(&previous.buckets);
+
+        if !same_bounds(&buckets, &before) {
+            return None;
+        }
+
+        let reset = buckets.iter().zip(&before).any(|(a, b)| a.count < b.count)
+            || matches!((self.count, previous.count), (Some(a), Some(b)) if a < b);
+
+        if reset {
+            return Some(Histogram {
+                buckets,
+                ..self.clone()
+            });
+        }
+
+        let buckets = buckets
+            .iter()
+            .zip(&before)
+            .map(|(a, b)| Bucket::new(a.upper_bound, a.count - b.count))
+            .collect();
+
+        let minus = |a: Option<f64>, b: Option<f64>| Some(a? - b.unwrap_or_default());
+
+        Some(Histogram {
+            labels: self.labels.clone(),
+            buckets,
+            count: minus(self.count, previous.count),
+            sum: minus(self.sum, previous.sum),
+            created: self.created,
+        })
+    }
+
+    /// Add the observations of `other` to this histogram
+    ///
+    /// Returns `None` if the bucket boundaries differ.  See [`rebucket()`](Self::rebucket)
+    ///
+    /// The result has no creation time.
+    pub fn merge(&self, other: &Histogram<'_>) -> Option<Histogram<'a>> {
+        let buckets = sorted(&self.buckets);
+        let others = sorted(&other.buckets);
+
+        if !same_bounds(&buckets, &others) {
+            return None;
+        }
+
+        let buckets = buckets
+            .iter()
+            .zip(&others)
+            .map(|(a, b)| Bucket::new(a.upper_bound, a.count + b.count))
+            .collect();
+
+        let plus = |a: Option<f64>, b: Option<f64>| Some(a? + b?);
+
+        Some(Histogram {
+            labels: self.labels.clone(),
+            buckets,
+            count: plus(self.count, other.count),
+            sum: plus(self.sum, other.sum),
+            created: None,
+        })
+    }
+
+    /// Keep only the buckets with upper bounds in `bounds`
+    ///
+    /// Cumulative buckets make this exact for boundaries the histogram has.  Returns `None` if
+    /// any of `bounds` is not a boundary of this histogram.
+    pub fn rebucket(&self, bounds: &[f64]) -> Option<Histogram<'a>> {
+        let buckets = bounds
+            .iter()
+            .map(|&bound| {
+                self.buckets
+                    .iter()
+                    .find(|bucket| bucket.upper_bound == bound)
+                    .copied()
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Histogram {
+            buckets: sorted(&buckets),
+            ..self.clone()
+        })
+    }
+
+    /// Estimate the `q` quantile like PromQL `histogram_quantile()`
+    ///
+    /// See [`bucket_quantile()`]
+    pub fn quantile(&self, q: f64) -> f64 {
+        bucket_quantile(q, &self.buckets)
+    }
+}
+
+impl<'a> Family<'a> {
+    /// Group the samples of a histogram family into [`Histogram`]s by labels
+    ///
+    /// Samples are grouped by the `_bucket`, `_count`, `_sum`, and `_created` suffixes of the
+    /// family name.  Samples with other names are ignored.  Buckets with a missing or invalid `le`
+    /// label are ignored.  Without a `_created` sample the OpenMetrics 2.0 start timestamp of any
+    /// sample is used.
+    pub fn histograms(&self) -> Vec<Histogram<'a>> {
+        self.group_histograms("_count", "_sum")
+    }
+
+    /// Group `_bucket` samples and samples with the `count` and `sum` suffixes by labels
+    pub(crate) fn group_histograms(&self, count: &str, sum: &str) -> Vec<Histogram<'a>> {
+        let name = self.name().unwrap_or_default();
+
+        let mut histograms: Vec<Histogram<'a>> = vec![];
+        let mut index: HashMap<Labels<'a>, usize> = HashMap::new();
+
+        for sample in &self.samples {
+            let Some(suffix) = sample.name().strip_prefix(name) else {
+                continue;
+            };
+
+            if !["_bucket", count, sum, "_created"].contains(&suffix) {
+                continue;
+            }
+
+            let mut labels = sample.labels().clone();
+            let le = labels.remove("le");
+
+            let i = *index.entry(labels.clone()).or_insert_with(|| {
+                histograms.push(Histogram::new(labels));
+
+                histograms.len() - 1
+            });
+
+            let histogram = &mut histograms[i];
+
+            if let Some(start_timestamp) = sample.start_timestamp() {
+                histogram.created.get_or_insert(start_timestamp);
+            }
+
+            if suffix == "_bucket" {
+                if let Some(upper_bound) = le.and_then(|le| le.parse().ok()) {
+                    histogram
+                        .buckets
+                        .push(Bucket::new(upper_bound, sample.number()));
+                }
+            } else if suffix == count {
+                histogram.count = Some(sample.number());
+            } else if suffix == sum {
+                histogram.sum = Some(sample.number());
+            } else if suffix == "_created" {
+                histogram.created = Some(sample.number());
+            }
+        }
+
+        histograms
+    }
+}
+
+/// Sum `histograms` grouped by their labels without the labels in `without`
+///
+/// Use this to aggregate away labels such as `instance` or `pod`.  When boundaries differ within
+/// a group the histograms are rebucketed onto the boundaries they all have.  Groups are in order
+/// of their first histogram.
+pub fn sum_histograms<'a>(histograms: &[Histogram<'a>], without: &[&str]) -> Vec<Histogram<'a>> {
+    let mut groups: Vec<(Labels<'a>, Vec<&Histogram<'a>>)> = vec![];
+    let mut index: HashMap<Labels<'a>, usize> = HashMap::new();
+
+    for histogram in histograms {
+        let mut labels = histogram.labels.clone();
+        labels.retain(|label| !without.contains(&label.name.as_ref()));
+
+        let i = *index.entry(labels.clone()).or_insert_with(|| {
+            groups.push((labels, vec![]));
+
+            groups.len() - 1
+        });
+
+        groups[i].1.push(histogram);
+    }
+
+    groups
+        .into_iter()
+        .filter_map(|(labels, histograms)| {
+            let bounds = common_bounds(&histograms);
+
+            let mut sum = Histogram {
+                buckets: bounds
+                    .iter()
+                    .map(|&bound| Bucket::new(bound, 0.0))
+                    .collect(),
+                count: Some(0.0),
+                sum: Some(0.0),
+                ..Histogram::new(labels)
+            };
+
+            for histogram in histograms {
+                sum = sum.merge(&histogram.rebucket(&bounds)?)?;
+            }
+
+            Some(sum)
+        })
+        .collect()
+}
+
+/// The sorted upper bounds all `histograms` have
+fn common_bounds(histograms: &[&Histogram<'_>]) -> Vec<f64> {
+    let Some((first, rest)) = histograms.split_first() else {
+        return vec![];
+    };
+
+    sorted(&first.buckets)
+        .iter()
+        .map(|bucket| bucket.upper_bound)
+        .filter(|&bound| {
+            rest.iter().all(|histogram| {
+                histogram
+                    .buckets
+                    .iter()
+                    .any(|bucket| bucket.upper_bound == bound)
+            })
+        })
+        .collect()
+}
+
+fn same_bounds(a: &[Bucket], b: &[Bucket]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.upper_bound == b.upper_bound)
+}
+
+pub(crate) fn sorted(buckets: &[Bucket]) -> Vec<Bucket> {
+    let mut sorted = buckets.to_vec();
+    sorted.sort_by(|a, b| a.upper_bound.total_cmp(&b.upper_bound));
+
+    sorted
+}
+
+/// Estimate the `q` quantile of cumulative `buckets` like PromQL `histogram_quantile()`
+///
+/// The quantile is interpolated linearly within the bucket containing it, with a lower bound of
+/// zero for the first bucket when its upper bound is positive.  Buckets with the same upper
+/// bound are merged and decreasing counts are raised to keep the buckets monotonic.
+///
+/// Returns `NaN` if `q` is `NaN`, there is no `+Inf` bucket, there are fewer than two buckets,
+/// or there are no observations.  Returns `-Inf` for `q < 0` and `+Inf` for `q > 1`.  When the
+/// quantile falls in the `+Inf` bucket the upper bound of the next bucket is returned.
+pub fn bucket_quantile(q: f64, buckets: &[Bucket]) -> f64 {
+    if q.is_nan() {
+        return f64::NAN;
+    }
+
+    if q < 0.0 {
+        return f64::NEG_INFINITY;
+    }
+
+    if q > 1.0 {
+        return f64::INFINITY;
+    }
+
+    let sorted = sorted(buckets);
+
+    if sorted
+        .last()
+        .is_none_or(|last| last.upper_bound != f64::INFINITY)
+    {
+        return f64::NAN;
+    }
+
+    let buckets = monotonic(coalesce(sorted));
+
+    if buckets.len() < 2 {
+        return f64::NAN;
+    }
+
+    let observations = buckets[buckets.len() - 1].count;
+
+    if observations == 0.0 {
+        return f64::NAN;
+    }
+
+    let mut rank = q * observations;
+
+    let b = buckets[..buckets.len() - 1]
+        .iter()
+        .position(|bucket| bucket.count >= rank)
+        .unwrap_or(buckets.len() - 1);
+
+    if b == buckets.len() - 1 {
+        return buckets[buckets.len() - 2].upper_bound;
+    }
+
+    if b == 0 && buckets[0].upper_bound <= 0.0 {
+        return buckets[0].upper_bound;
+    }
+
+    let mut start = 0.0;
+    let end = buckets[b].upper_bound;
+    let mut count = buckets[b].count;
+
+    if b > 0 {
+        start = buckets[b - 1].upper_bound;
+        count -= buckets[b - 1].count;
+        rank -= buckets[b - 1].count;
+    }
+
+    start + (end - start) * (rank / count)
+}
+
+/// Merge sorted buckets with the same upper bound
+fn coalesce(buckets: Vec<Bucket>) -> Vec<Bucket> {
+    let mut coalesced: Vec<Bucket> = Vec::with_capacity(buckets.len());
+
+    for bucket in buckets {
+        match coalesced.last_mut() {
+            Some(last) if last.upper_bound == bucket.upper_bound => last.count += bucket.count,
+            _ => coalesced.push(bucket),
+        }
+    }
+
+    coalesced
+}
+
+/// Raise decreasing bucket counts to the largest previous count
+fn monotonic(mut buckets: Vec<Bucket>) -> Vec<Bucket> {
+    let mut max = f64::NAN;
+
+    for bucket in &mut buckets {
+        if bucket.count > max || max.is_nan() {
+            max = bucket.count;
+        } else if bucket.count < max {
+            bucket.count = max;
+        }
+    }
+
+    buckets
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parser::prometheus, test::parse};
+    use assert_float_eq::assert_float_absolute_eq;
+    use rstest::rstest;
+
+    const INF: f64 = f64::INFINITY;
+
+    fn buckets(buckets: &[(f64, f64)]) -> Vec<Bucket> {
+        buckets
+            .iter()
+            .map(|&(upper_bound, count)| Bucket::new(upper_bound, count))
+            .collect()
+    }
+
+    #[rstest]
+    #[case(0.5, &[(0.1, 10.0), (0.5, 50.0), (1.0, 90.0), (INF, 100.0)], 0.5)]
+    #[case(0.9, &[(0.1, 10.0), (0.5, 50.0), (1.0, 90.0), (INF, 100.0)], 1.0)]
+    #[case(0.95, &[(0.1, 10.0), (0.5, 50.0), (1.0, 90.0), (INF, 100.0)], 1.0)]
+    #[case(0.05, &[(0.1, 10.0), (0.5, 50.0), (1.0, 90.0), (INF, 100.0)], 0.05)]
+    #[case(0.3, &[(0.1, 10.0), (0.5, 50.0), (1.0, 90.0), (INF, 100.0)], 0.3)]
+    #[case(0.0, &[(0.1, 10.0), (INF, 100.0)], 0.0)]
+    #[case(1.0, &[(0.1, 10.0), (0.5, 50.0), (INF, 50.0)], 0.5)]
+    #[case(0.5, &[(-1.0, 10.0), (0.0, 20.0), (INF, 20.0)], -1.0)]
+    #[case(0.75, &[(-1.0, 10.0), (0.0, 20.0), (INF, 20.0)], -0.5)]
+    #[case(0.5, &[(INF, 100.0), (1.0, 90.0), (0.1, 10.0), (0.5, 50.0)], 0.5)]
+    fn bucket_quantile(#[case] q: f64, #[case] input: &[(f64, f64)], #[case] expected: f64) {
+        let actual = super::bucket_quantile(q, &buckets(input));
+
+        assert_float_absolute_eq!(expected, actual, 1e-9);
+    }
+
+    #[rstest]
+    #[case(f64::NAN, &[(1.0, 1.0), (INF, 1.0)])]
+    #[case(0.5, &[(1.0, 1.0), (2.0, 1.0)])]
+    #[case(0.5, &[(INF, 1.0)])]
+    #[case(0.5, &[(1.0, 0.0), (INF, 0.0)])]
+    #[case(0.5, &[])]
+    fn bucket_quantile_nan(#[case] q: f64, #[case] input: &[(f64, f64)]) {
+        assert!(super::bucket_quantile(q, &buckets(input)).is_nan());
+    }
+
+    #[test]
+    fn bucket_quantile_out_of_range() {
+        let buckets = buckets(&[(1.0, 1.0), (INF, 1.0)]);
+
+        assert_eq!(f64::NEG_INFINITY, super::bucket_quantile(-0.1, &buckets));
+        assert_eq!(INF, super::bucket_quantile(1.1, &buckets));
+    }
+
+    #[test]
+    fn bucket_quantile_repairs_buckets() {
+        let repaired = buckets(&[(0.1, 10.0), (0.5, 50.0), (1.0, 90.0), (INF, 100.0)]);
+        let broken = buckets(&[
+            (0.1, 10.0),
+            (0.5, 20.0),
+            (0.5, 30.0),
+            (1.0, 90.0),
+            (INF, 100.0),
+        ]);
+
+        for q in [0.25, 0.5, 0.9] {
+            assert_float_absolute_eq!(
+                super::bucket_quantile(q, &repaired),
+                super::bucket_quantile(q, &broken)
+            );
+        }
+
+        let decreasing = buckets(&[(0.1, 10.0), (0.5, 5.0), (INF, 20.0)]);
+
+        assert_float_absolute_eq!(0.05, super::bucket_quantile(0.25, &decreasing));
+        assert_float_absolute_eq!(0.5, super::bucket_quantile(0.75, &decreasing));
+    }
+
+    const INPUT: &str = "# TYPE latency histogram
+latency_bucket{path=\"/\",le=\"0.1\"} 10
+latency_bucket{path=\"/\",le=\"0.5\"} 50
+latency_bucket{path=\"/\",le=\"+Inf\"} 100
+latency_count{path=\"/\"} 100
+latency_sum{path=\"/\"} 27.5
+latency_created{path=\"/\"} 1700000000
+latency_bucket{path=\"/a\",le=\"0.1\"} 1
+latency_bucket{path=\"/a\",le=\"+Inf\"} 2
+latency_count{path=\"/a\"} 2
+latency_sum{path=\"/a\"} 0.5
+";
+
+    #[test]
+    fn histograms() {
+        let (_, families) = parse(prometheus, INPUT);
+
+        let histograms = families[0].histograms();
+
+        assert_eq!(
+            vec![
+                Histogram {
+                    labels: Labels::new().with("path", "/"),
+                    buckets: buckets(&[(0.1, 10.0), (0.5, 50.0), (INF, 100.0)]),
+                    count: Some(100.0),
+                    sum: Some(27.5),
+                    created: Some(1700000000.0),
+                },
+                Histogram {
+                    labels: Labels::new().with("path", "/a"),
+                    buckets: buckets(&[(0.1, 1.0), (INF, 2.0)]),
+                    count: Some(2.0),
+                    sum: Some(0.5),
+                    created: None,
+                },
+            ],
+            histograms
+        );
+
+        assert_float_absolute_eq!(0.3, histograms[0].quantile(0.3));
+    }
+
+    #[test]
+    fn histograms_unknown_suffix() {
+        let input =
+            "# TYPE latency histogram\nlatency 1\nlatency_max 2\nlatency_count{path=\"/\"} 3\n";
+        let (_, families) = parse(prometheus, input);
+
+        assert_eq!(
+            vec![Histogram {
+                count: Some(3.0),
+                ..Histogram::new(Labels::new().with("path", "/"))
+            }],
+            families[0].histograms()
+        );
+    }
+
+    #[test]
+    fn delta() {
+        let (_, previous) = parse(prometheus, INPUT);
+        let input = INPUT
+            .replace("le=\"0.1\"} 10", "le=\"0.1\"} 12")
+            .replace("le=\"0.5\"} 50", "le=\"0.5\"} 60")
+            .replace("le=\"+Inf\"} 100", "le=\"+Inf\"} 120")
+            .replace(
+                "latency_count{path=\"/\"} 100",
+                "latency_count{path=\"/\"} 120",
+            );
+        let (_, current) = parse(prometheus, &input);
+
+        let previous = &previous[0].histograms()[0];
+        let current = &current[0].histograms()[0];
+
+        let delta = current.delta(previous).unwrap();
+
+        assert_eq!(
+            buckets(&[(0.1, 2.0), (0.5, 10.0), (INF, 20.0)]),
+            delta.buckets
+        );
+        assert_eq!(Some(20.0), delta.count);
+        assert_eq!(Some(0.0), delta.sum);
+        assert_float_absolute_eq!(0.5, delta.quantile(0.5));
+        assert_float_absolute_eq!(0.1 + 0.4 * (3.0 / 8.0), delta.quantile(0.25));
+    }
+
+    fn histogram(labels: Labels<'static>, input: &[(f64, f64)]) -> Histogram<'static> {
+        Histogram {
+            buckets: buckets(input),
+            count: input.iter().map(|&(_, count)| count).reduce(f64::max),
+            sum: Some(1.0),
+            ..Histogram::new(labels)
+        }
+    }
+
+    #[test]
+    fn merge() {
+        let a = histogram(Labels::new(), &[(1.0, 1.0), (INF, 2.0)]);
+        let b = histogram(Labels::new(), &[(INF, 5.0), (1.0, 3.0)]);
+
+        assert_eq!(
+            Some(Histogram {
+                sum: Some(2.0),
+                ..histogram(Labels::new(), &[(1.0, 4.0), (INF, 7.0)])
+            }),
+            a.merge(&b)
+        );
+
+        let c = histogram(Labels::new(), &[(2.0, 3.0), (INF, 5.0)]);
+
+        assert_eq!(None, a.merge(&c));
+    }
+
+    #[test]
+    fn rebucket() {
+        let fine = histogram(
+            Labels::new(),
+            &[(0.1, 1.0), (0.5, 3.0), (1.0, 6.0), (INF, 7.0)],
+        );
+
+        assert_eq!(
+            Some(buckets(&[(0.5, 3.0), (INF, 7.0)])),
+            fine.rebucket(&[INF, 0.5]).map(|h| h.buckets)
+        );
+        assert_eq!(None, fine.rebucket(&[0.25, INF]));
+    }
+
+    #[test]
+    fn sum_histograms() {
+        let labels = |service, pod| Labels::new().with("service", service).with("pod", pod);
+
+        let histograms = [
+            histogram(labels("api", "a"), &[(0.1, 1.0), (0.5, 3.0), (INF, 4.0)]),
+            histogram(labels("web", "a"), &[(1.0, 1.0), (INF, 1.0)]),
+            histogram(labels("api", "b"), &[(0.5, 2.0), (1.0, 5.0), (INF, 6.0)]),
+        ];
+
+        let sums = super::sum_histograms(&histograms, &["pod"]);
+
+        assert_eq!(
+            vec![
+                Histogram {
+                    sum: Some(2.0),
+                    ..histogram(
+                        Labels::new().with("service", "api"),
+                        &[(0.5, 5.0), (INF, 10.0)]
+                    )
+                },
+                histogram(
+                    Labels::new().with("service", "web"),
+                    &[(1.0, 1.0), (INF, 1.0)]
+                ),
+            ],
+            sums
+        );
+    }
+
+    #[test]
+    fn delta_reset() {
+        let previous = Histogram {
+            buckets: buckets(&[(1.0, 10.0), (INF, 20.0)]),
+            ..Histogram::new(Labels::new())
+        };
+        let current = Histogram {
+            buckets: buckets(&[(1.0, 1.0), (INF, 2.0)]),
+            ..Histogram::new(Labels::new())
+        };
+
+        assert_eq!(Some(current.clone()), current.delta(&previous));
+
+        let unsorted = Histogram {
+            buckets: buckets(&[(INF, 2.0), (1.0, 1.0)]),
+            ..Histogram::new(Labels::new())
+        };
+
+        assert_eq!(Some(current), unsorted.delta(&previous));
+
+        let rebucketed = Histogram {
+            buckets: buckets(&[(2.0, 1.0), (INF, 2.0)]),
+            ..Histogram::new(Labels::new())
+        };
+
+        assert_eq!(None, rebucketed.delta(&previous));
+    }
+}
@@ -0,0 +1,204 @@
+use crate::{Exposition, Labels, MetricType, ValidationError};
+use std::collections::{hash_map::Entry, HashMap};
+
+impl Exposition<'_> {
+    /// Copy labels from the series of an info family onto all other series, like PromQL
+    /// `* on(job, instance) group_left(version) build_info`
+    ///
+    /// `info` is the family or sample name of the info family, such as `target_info`.  Series
+    /// are joined when the values of the `on` labels are equal, where a missing label has an
+    /// empty value.  The `include` labels of the info series are copied, replacing existing
+    /// labels.  Like `group_left()` an empty `include` copies no labels.
+    ///
+    /// Returns the number of series that were joined, or zero if there is no family `info`.
+    /// Returns an error if the family is not an info family or if several of its series have the
+    /// same `on` values, and then no labels are copied.
+    ///
+    /// ```
+    /// use nom_openmetrics::{parser::openmetrics, Exposition};
+    ///
+    /// let input = concat!(
+    ///     "# TYPE target info\n",
+    ///     "target_info{job=\"api\",instance=\"a\",version=\"1.2\"} 1\n",
+    ///     "# TYPE up gauge\n",
+    ///     "up{job=\"api\",instance=\"a\"} 1\n",
+    ///     "# EOF\n",
+    /// );
+    ///
+    /// let mut exposition = Exposition::from(openmetrics(input).unwrap().1);
+    ///
+    /// exposition.join_info("target_info", &["job", "instance"], &["version"]).unwrap();
+    ///
+    /// let up = &exposition.family("up").unwrap().samples[0];
+    ///
+    /// assert_eq!(Some("1.2"), up.labels().get("version"));
+    /// ```
+    pub fn join_info(
+        &mut self,
+        info: &str,
+        on: &[&str],
+        include: &[&str],
+    ) -> Result<usize, ValidationError> {
+        let Some(position) = self
+            .family_position(info)
+            .or_else(|| self.sample_family_position(info))
+        else {
+            return Ok(0);
+        };
+
+        let family = &self.families()[position];
+
+        family.expect_type(MetricType::Info)?;
+
+        let mut joins: HashMap<Vec<String>, Vec<(String, String)>> = HashMap::new();
+
+        for sample in &family.samples {
+            let Entry::Vacant(entry) = joins.entry(key(sample.labels(), on)) else {
+                return Err(ValidationError::DuplicateSeries {
+                    sample_line: sample.to_string(),
+                });
+            };
+
+            let labels = sample
+                .labels()
+                .iter()
+                .filter(|label| {
+                    let name = label.name.as_ref();
+
+                    !on.contains(&name) && include.contains(&name)
+                })
+                .map(|label| (label.name.to_string(), label.value.clone()))
+                .collect();
+
+            entry.insert(labels);
+        }
+
+        let mut joined = 0;
+
+        for (i, family) in self.families_mut().iter_mut().enumerate() {
+            if i == position {
+                continue;
+            }
+
+            for sample in &mut family.samples {
+                let Some(labels) = joins.get(&key(sample.labels(), on)) else {
+                    continue;
+                };
+
+                for (name, value) in labels {
+                    sample.labels_mut().set(name.clone(), value.clone());
+                }
+
+                joined += 1;
+            }
+        }
+
+        Ok(joined)
+    }
+}
+
+/// The values of the `on` labels
+fn key(labels: &Labels<'_>, on: &[&str]) -> Vec<String> {
+    on.iter()
+        .map(|name| labels.get(name).unwrap_or_default().to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{parser::openmetrics, test::parse, Exposition, Labels, ValidationError};
+
+    const INPUT: &str = "# TYPE target info
+target_info{job=\"api\",instance=\"a\",version=\"1.2\",region=\"us\"} 1
+target_info{job=\"api\",instance=\"b\",version=\"1.3\",region=\"eu\"} 1
+# TYPE up gauge
+up{job=\"api\",instance=\"a\"} 1
+up{job=\"api\",instance=\"b\",version=\"old\"} 1
+up{job=\"api\",instance=\"c\"} 0
+# EOF
+";
+
+    fn labels<'a>(exposition: &Exposition<'a>) -> Vec<Labels<'a>> {
+        let up = exposition.family("up").unwrap();
+
+        up.samples
+            .iter()
+            .map(|sample| sample.labels().clone())
+            .collect()
+    }
+
+    #[test]
+    fn join_info() {
+        let (_, families) = parse(openmetrics, INPUT);
+        let mut exposition = Exposition::from(families);
+
+        let joined = exposition.join_info("target_info", &["job", "instance"], &["version"]);
+
+        assert_eq!(Ok(2), joined);
+        assert_eq!(
+            vec![
+                Labels::new()
+                    .with("job", "api")
+                    .with("instance", "a")
+                    .with("version", "1.2"),
+                Labels::new()
+                    .with("job", "api")
+                    .with("instance", "b")
+                    .with("version", "1.3"),
+                Labels::new().with("job", "api").with("instance", "c"),
+            ],
+            labels(&exposition)
+        );
+    }
+
+    #[test]
+    fn join_info_empty_include() {
+        let (_, families) = parse(openmetrics, INPUT);
+        let mut exposition = Exposition::from(families);
+        let before = labels(&exposition);
+
+        assert_eq!(
+            Ok(2),
+            exposition.join_info("target", &["job", "instance"], &[])
+        );
+        assert_eq!(before, labels(&exposition));
+    }
+
+    #[test]
+    fn join_info_missing() {
+        let (_, families) = parse(openmetrics, INPUT);
+        let mut exposition = Exposition::from(families);
+
+        assert_eq!(Ok(0), exposition.join_info("build_info", &["job"], &[]));
+    }
+
+    #[test]
+    fn join_info_not_info() {
+        let (_, families) = parse(openmetrics, INPUT);
+        let mut exposition = Exposition::from(families);
+
+        assert_eq!(
+            Err(ValidationError::WrongType {
+                family: "up".into(),
+                expected: "info".into()
+            }),
+            exposition.join_info("up", &["job"], &[])
+        );
+    }
+
+    #[test]
+    fn join_info_duplicate() {
+        let (_, families) = parse(openmetrics, INPUT);
+        let mut exposition = Exposition::from(families);
+        let before = labels(&exposition);
+
+        assert_eq!(
+            Err(ValidationError::DuplicateSeries {
+                sample_line:
+                    "target_info{job=\"api\",instance=\"b\",version=\"1.3\",region=\"eu\"} 1".into()
+            }),
+            exposition.join_info("target", &["job"], &["version"])
+        );
+        assert_eq!(before, labels(&exposition));
+    }
+}
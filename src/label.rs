@@ -1,13 +1,49 @@
+use crate::parser::label::is_metric_label;
+use std::{borrow::Cow, fmt};
+
 /// A label for a metric
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Label<'a> {
-    pub name: &'a str,
+    pub name: Cow<'a, str>,
     pub value: String,
 }
 
 impl<'a> Label<'a> {
     /// Create a `Label`
-    pub fn new(name: &'a str, value: String) -> Self {
-        Self { name, value }
+    pub fn new(name: impl Into<Cow<'a, str>>, value: String) -> Self {
+        Self {
+            name: name.into(),
+            value,
+        }
+    }
+}
+
+impl fmt::Display for Label<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if is_metric_label(&self.name) {
+            f.write_str(&self.name)?;
+        } else {
+            f.write_str("\"")?;
+            fmt_escaped(&self.name, f)?;
+            f.write_str("\"")?;
+        }
+
+        f.write_str("=\"")?;
+        fmt_escaped(&self.value, f)?;
+        f.write_str("\"")
     }
 }
+
+/// Write `value` with quotes, backslashes, and newlines escaped
+pub(crate) fn fmt_escaped(value: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for c in value.chars() {
+        match c {
+            '\\' => f.write_str("\\\\")?,
+            '"' => f.write_str("\\\"")?,
+            '\n' => f.write_str("\\n")?,
+            c => write!(f, "{c}")?,
+        }
+    }
+
+    Ok(())
+}
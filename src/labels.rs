@@ -0,0 +1,252 @@
+use crate::Label;
+use std::{
+    borrow::Cow,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Deref,
+};
+
+/// The [`Label`]s of a sample
+///
+/// Labels keep exposition order.  Equality and hashing do not depend on order.
+#[derive(Clone, Debug, Default)]
+pub struct Labels<'a>(Vec<Label<'a>>);
+
+impl<'a> Labels<'a> {
+    /// Create empty `Labels`
+    pub fn new() -> Self {
+        Self(vec![])
+    }
+
+    /// Returns `true` if there is a label named `name`
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// The value of the label named `name`
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|label| label.name == name)
+            .map(|label| label.value.as_str())
+    }
+
+    /// Consume `Labels` returning a `Vec` of [`Label`]s
+    pub fn into_vec(self) -> Vec<Label<'a>> {
+        self.0
+    }
+
+    /// Add a label
+    pub fn push(&mut self, name: impl Into<Cow<'a, str>>, value: &str) {
+        self.0.push(Label::new(name, value.into()));
+    }
+
+    /// Remove the label named `name` returning its value
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        let index = self.0.iter().position(|label| label.name == name)?;
+
+        Some(self.0.remove(index).value)
+    }
+
+    /// Keep only labels for which `f` returns `true`
+    pub fn retain(&mut self, f: impl FnMut(&Label<'a>) -> bool) {
+        self.0.retain(f);
+    }
+
+    /// Set the value of the label named `name`, adding it if it is missing
+    pub fn set(&mut self, name: impl Into<Cow<'a, str>>, value: String) {
+        let name = name.into();
+
+        match self.0.iter_mut().find(|label| label.name == name) {
+            Some(label) => label.value = value,
+            None => self.0.push(Label::new(name, value)),
+        }
+    }
+
+    /// The labels sorted by name
+    pub fn sorted(&self) -> impl Iterator<Item = &Label<'a>> {
+        let mut labels: Vec<_> = self.0.iter().collect();
+
+        labels.sort_by(|a, b| (&a.name, &a.value).cmp(&(&b.name, &b.value)));
+
+        labels.into_iter()
+    }
+
+    /// Add a label to `Labels`
+    pub fn with(mut self, name: impl Into<Cow<'a, str>>, value: &str) -> Self {
+        self.push(name, value);
+
+        self
+    }
+}
+
+impl<'a> Deref for Labels<'a> {
+    type Target = [Label<'a>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl fmt::Display for Labels<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+
+        f.write_str("{")?;
+
+        for (i, label) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+
+            write!(f, "{label}")?;
+        }
+
+        f.write_str("}")
+    }
+}
+
+impl<'a> From<Vec<Label<'a>>> for Labels<'a> {
+    fn from(labels: Vec<Label<'a>>) -> Self {
+        Self(labels)
+    }
+}
+
+impl<'a> FromIterator<Label<'a>> for Labels<'a> {
+    fn from_iter<T: IntoIterator<Item = Label<'a>>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<'s, 'a> IntoIterator for &'s Labels<'a> {
+    type Item = &'s Label<'a>;
+    type IntoIter = std::slice::Iter<'s, Label<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl Eq for Labels<'_> {}
+
+impl Hash for Labels<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.0.len());
+
+        for label in self.sorted() {
+            label.hash(state);
+        }
+    }
+}
+
+impl PartialEq for Labels<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len() && self.sorted().eq(other.sorted())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn get() {
+        let labels = Labels::new()
+            .with("job", "prometheus")
+            .with("instance", "localhost:9090");
+
+        assert_eq!(Some("prometheus"), labels.get("job"));
+        assert_eq!(Some("localhost:9090"), labels.get("instance"));
+        assert_eq!(None, labels.get("le"));
+        assert!(labels.contains("job"));
+        assert!(!labels.contains("le"));
+    }
+
+    #[test]
+    fn set() {
+        let mut labels = Labels::new().with("job", "prometheus");
+
+        labels.set("job", "api".into());
+        labels.set("instance", "a".into());
+
+        assert_eq!(
+            Labels::new().with("job", "api").with("instance", "a"),
+            labels
+        );
+        assert_eq!(Some("a".into()), labels.remove("instance"));
+        assert_eq!(None, labels.remove("instance"));
+
+        labels.retain(|label| label.name != "job");
+
+        assert!(labels.is_empty());
+    }
+
+    #[test]
+    fn sorted() {
+        let labels = Labels::new()
+            .with("job", "prometheus")
+            .with("instance", "localhost:9090");
+
+        assert_eq!(
+            vec!["instance", "job"],
+            labels
+                .sorted()
+                .map(|label| label.name.as_ref())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec!["job", "instance"],
+            labels
+                .iter()
+                .map(|label| label.name.as_ref())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn eq() {
+        let a = Labels::new()
+            .with("job", "prometheus")
+            .with("instance", "a");
+        let b = Labels::new()
+            .with("instance", "a")
+            .with("job", "prometheus");
+        let c = Labels::new()
+            .with("instance", "b")
+            .with("job", "prometheus");
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_ne!(a, Labels::new().with("job", "prometheus"));
+    }
+
+    #[test]
+    fn hash() {
+        use std::collections::HashSet;
+
+        let a = Labels::new()
+            .with("job", "prometheus")
+            .with("instance", "a");
+        let b = Labels::new()
+            .with("instance", "a")
+            .with("job", "prometheus");
+
+        let series: HashSet<_> = [a, b].into_iter().collect();
+
+        assert_eq!(1, series.len());
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("", Labels::new().to_string());
+        assert_eq!(
+            "{job=\"a\",le=\"+Inf\"}",
+            Labels::new()
+                .with("job", "a")
+                .with("le", "+Inf")
+                .to_string()
+        );
+    }
+}
@@ -1,12 +1,49 @@
+mod aggregation;
+mod counter;
+mod escaping;
+mod exposition;
 mod family;
+mod fingerprint;
+mod gauge_histogram;
+mod histogram;
+mod info;
 mod label;
+mod labels;
+mod limits;
+mod metadata;
 mod metric_descriptor;
+mod native_histogram;
 pub mod parser;
+pub mod rate;
+pub mod relabel;
 mod sample;
+mod selector;
+mod stateset;
+mod summary;
+mod syntax;
 #[cfg(test)]
 mod test;
+mod validation;
+mod visitor;
 
+pub use aggregation::{Aggregation, Grouping, Operator};
+pub use counter::Counter;
+pub use escaping::Escaping;
+pub use exposition::Exposition;
 pub use family::Family;
+pub use fingerprint::Fingerprint;
+pub use gauge_histogram::GaugeHistogram;
+pub use histogram::{bucket_quantile, sum_histograms, Bucket, Histogram};
 pub use label::Label;
+pub use labels::Labels;
+pub use limits::{Limit, Limits};
+pub use metadata::Metadata;
 pub use metric_descriptor::{MetricDescriptor, MetricType};
+pub use native_histogram::{NativeBucket, NativeHistogram, Span};
 pub use sample::Sample;
+pub use selector::{LabelMatcher, MatchOp, Selector};
+pub use stateset::{State, Stateset};
+pub use summary::{Quantile, Summary};
+pub use syntax::{Line, LineKind, SyntaxTree};
+pub use validation::ValidationError;
+pub use visitor::Visitor;
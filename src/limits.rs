@@ -0,0 +1,67 @@
+/// Limits on the size of a parsed exposition
+///
+/// Parsing stops with [`Error::Limit`](crate::parser::Error::Limit) when a limit is exceeded.  Each
+/// limit is unset (unlimited) by default.
+///
+/// Limits are checked while a line is parsed, before the labels, label values, or HELP text that
+/// would exceed them are allocated.  The families limit is checked when a family starts.
+///
+/// ```
+/// use nom_openmetrics::Limits;
+///
+/// let limits = Limits {
+///     max_line_length: Some(64 * 1024),
+///     max_samples: Some(50_000),
+///     ..Limits::default()
+/// };
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Limits {
+    /// Maximum length of a line in bytes, excluding the newline
+    pub max_line_length: Option<usize>,
+    /// Maximum number of labels on a sample
+    pub max_labels: Option<usize>,
+    /// Maximum length of a label name in bytes
+    pub max_label_name_length: Option<usize>,
+    /// Maximum length of a label value in bytes
+    pub max_label_value_length: Option<usize>,
+    /// Maximum number of samples in an exposition
+    pub max_samples: Option<usize>,
+    /// Maximum number of families in an exposition
+    pub max_families: Option<usize>,
+    /// Maximum length of HELP text in bytes
+    pub max_help_length: Option<usize>,
+}
+
+impl Limits {
+    /// Create `Limits` with no limits set
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// The limit in [`Limits`] that was exceeded
+#[derive(Clone, Copy, Debug, Eq, PartialEq, strum::Display)]
+pub enum Limit {
+    /// [`Limits::max_line_length`]
+    #[strum(to_string = "line length")]
+    LineLength,
+    /// [`Limits::max_labels`]
+    #[strum(to_string = "labels per sample")]
+    Labels,
+    /// [`Limits::max_label_name_length`]
+    #[strum(to_string = "label name length")]
+    LabelNameLength,
+    /// [`Limits::max_label_value_length`]
+    #[strum(to_string = "label value length")]
+    LabelValueLength,
+    /// [`Limits::max_samples`]
+    #[strum(to_string = "samples")]
+    Samples,
+    /// [`Limits::max_families`]
+    #[strum(to_string = "families")]
+    Families,
+    /// [`Limits::max_help_length`]
+    #[strum(to_string = "help length")]
+    HelpLength,
+}
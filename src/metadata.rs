@@ -0,0 +1,22 @@
+use crate::MetricDescriptor;
+
+/// A metric's [descriptor](MetricDescriptor)s and the number of series in its family
+#[derive(Debug, PartialEq)]
+pub struct Metadata<'a> {
+    /// The HELP, TYPE, and UNIT descriptors of the family in exposition order
+    pub descriptors: Vec<MetricDescriptor<'a>>,
+    /// The number of samples in the family
+    ///
+    /// Each bucket, quantile, `_sum`, and `_count` sample counts as a separate series.
+    pub series: usize,
+}
+
+impl<'a> Metadata<'a> {
+    /// Create a `Metadata`
+    pub fn new(descriptors: Vec<MetricDescriptor<'a>>, series: usize) -> Self {
+        Self {
+            descriptors,
+            series,
+        }
+    }
+}
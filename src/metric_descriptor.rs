@@ -1,35 +1,56 @@
+use crate::{label::fmt_escaped, parser::metric_name::is_metric_name};
+use std::{borrow::Cow, fmt};
+
 /// The HELP, TYPE, and UNIT of a metric
 #[derive(Debug, PartialEq)]
 pub enum MetricDescriptor<'a> {
     /// The `MetricType`
     Type {
-        metric: &'a str,
+        metric: Cow<'a, str>,
         r#type: MetricType<'a>,
     },
     /// The metric description
-    Help { metric: &'a str, help: String },
+    Help { metric: Cow<'a, str>, help: String },
     /// The metric unit
-    Unit { metric: &'a str, unit: &'a str },
+    Unit { metric: Cow<'a, str>, unit: &'a str },
 }
 
 impl<'a> MetricDescriptor<'a> {
     /// Crate a HELP descriptor
-    pub fn help(metric: &'a str, help: String) -> Self {
-        Self::Help { metric, help }
+    pub fn help(metric: impl Into<Cow<'a, str>>, help: String) -> Self {
+        Self::Help {
+            metric: metric.into(),
+            help,
+        }
     }
 
     /// Crate a TYPE descriptor
-    pub fn r#type(metric: &'a str, r#type: MetricType<'a>) -> Self {
-        Self::Type { metric, r#type }
+    pub fn r#type(metric: impl Into<Cow<'a, str>>, r#type: MetricType<'a>) -> Self {
+        Self::Type {
+            metric: metric.into(),
+            r#type,
+        }
     }
 
     /// Crate a UNIT descriptor
-    pub fn unit(metric: &'a str, unit: &'a str) -> Self {
-        Self::Unit { metric, unit }
+    pub fn unit(metric: impl Into<Cow<'a, str>>, unit: &'a str) -> Self {
+        Self::Unit {
+            metric: metric.into(),
+            unit,
+        }
+    }
+
+    /// Set the metric name
+    pub fn set_metric(&mut self, name: impl Into<Cow<'a, str>>) {
+        match self {
+            MetricDescriptor::Type { metric, .. }
+            | MetricDescriptor::Help { metric, .. }
+            | MetricDescriptor::Unit { metric, .. } => *metric = name.into(),
+        }
     }
 
     /// The metric name
-    pub fn metric(&self) -> &'a str {
+    pub fn metric(&self) -> &str {
         match self {
             MetricDescriptor::Type { metric, .. }
             | MetricDescriptor::Help { metric, .. }
@@ -38,6 +59,59 @@ impl<'a> MetricDescriptor<'a> {
     }
 }
 
+impl fmt::Display for MetricDescriptor<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keyword = match self {
+            MetricDescriptor::Type { .. } => "TYPE",
+            MetricDescriptor::Help { .. } => "HELP",
+            MetricDescriptor::Unit { .. } => "UNIT",
+        };
+
+        write!(f, "# {keyword} ")?;
+
+        let metric = self.metric();
+
+        if is_metric_name(metric) {
+            f.write_str(metric)?;
+        } else {
+            f.write_str("\"")?;
+            fmt_escaped(metric, f)?;
+            f.write_str("\"")?;
+        }
+
+        match self {
+            MetricDescriptor::Type { r#type, .. } => write!(f, " {}", r#type.as_str()),
+            MetricDescriptor::Help { help, .. } => {
+                f.write_str(" ")?;
+
+                for c in help.chars() {
+                    match c {
+                        '\\' => f.write_str("\\\\")?,
+                        '\n' => f.write_str("\\n")?,
+                        c => write!(f, "{c}")?,
+                    }
+                }
+
+                Ok(())
+            }
+            MetricDescriptor::Unit { unit, .. } => write!(f, " {unit}"),
+        }
+    }
+}
+
+/// Suffixes of sample names that belong to a family without the suffix
+pub(crate) const SUFFIXES: [&str; 8] = [
+    "_bucket", "_count", "_sum", "_created", "_total", "_gcount", "_gsum", "_info",
+];
+
+/// `name` without the first of `suffixes` it ends with
+pub(crate) fn strip_suffix<'n>(name: &'n str, suffixes: &[&str]) -> &'n str {
+    suffixes
+        .iter()
+        .find_map(|suffix| name.strip_suffix(suffix))
+        .unwrap_or(name)
+}
+
 /// The type of the metric
 #[derive(Debug, PartialEq, strum::Display)]
 pub enum MetricType<'a> {
@@ -59,3 +133,31 @@ pub enum MetricType<'a> {
     #[strum(to_string = "{0}")]
     Unknown(&'a str),
 }
+
+impl<'a> MetricType<'a> {
+    /// The type as written in an exposition
+    pub fn as_str(&self) -> &'a str {
+        match self {
+            MetricType::Counter => "counter",
+            MetricType::Gauge => "gauge",
+            MetricType::Gaugehistogram => "gaugehistogram",
+            MetricType::Histogram => "histogram",
+            MetricType::Info => "info",
+            MetricType::Stateset => "stateset",
+            MetricType::Summary => "summary",
+            MetricType::Unknown(r#type) => r#type,
+        }
+    }
+
+    /// Suffixes of sample names in a family of this type
+    pub(crate) fn suffixes(&self) -> &'static [&'static str] {
+        match self {
+            MetricType::Counter => &["_total", "_created"],
+            MetricType::Gaugehistogram => &["_bucket", "_gcount", "_gsum"],
+            MetricType::Histogram => &["_bucket", "_count", "_sum", "_created"],
+            MetricType::Info => &["_info"],
+            MetricType::Summary => &["_count", "_sum", "_created"],
+            MetricType::Gauge | MetricType::Stateset | MetricType::Unknown(_) => &[],
+        }
+    }
+}
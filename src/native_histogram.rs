@@ -0,0 +1,658 @@
+use crate::{sample::fmt_number, Bucket, Histogram, Labels};
+use std::fmt;
+
+/// The coarsest standard exponential schema
+pub(crate) const MIN_SCHEMA: i32 = -4;
+
+/// The finest standard exponential schema
+pub(crate) const MAX_SCHEMA: i32 = 8;
+
+/// A run of consecutive native histogram buckets
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Span {
+    /// The index of the first bucket for the first span, or the gap after the previous span
+    pub offset: i32,
+    /// The number of buckets
+    pub length: u32,
+}
+
+impl Span {
+    /// Create a `Span`
+    pub fn new(offset: i32, length: u32) -> Self {
+        Self { offset, length }
+    }
+}
+
+/// A native histogram bucket with its boundaries
+///
+/// Positive buckets include their upper bound, negative buckets include their lower bound, and
+/// the zero bucket includes both bounds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NativeBucket {
+    /// The lower bound
+    pub lower: f64,
+    /// The upper bound
+    pub upper: f64,
+    /// The number of observations in the bucket
+    pub count: f64,
+}
+
+/// A native (sparse, exponential) histogram
+///
+/// Bucket boundaries are powers of `2^(2^-schema)`.  Bucket counts are absolute, not deltas.
+/// The schema must be from -4 to 8; a histogram with another schema has no buckets.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NativeHistogram {
+    /// The number of observations
+    pub count: f64,
+    /// The sum of observations
+    pub sum: f64,
+    /// The resolution of the buckets, from -4 to 8
+    pub schema: i32,
+    /// The upper bound of the zero bucket
+    pub zero_threshold: f64,
+    /// The number of observations in the zero bucket
+    pub zero_count: f64,
+    /// Spans of positive buckets
+    pub positive_spans: Vec<Span>,
+    /// Counts of positive buckets
+    pub positive_buckets: Vec<f64>,
+    /// Spans of negative buckets
+    pub negative_spans: Vec<Span>,
+    /// Counts of negative buckets
+    pub negative_buckets: Vec<f64>,
+}
+
+impl NativeHistogram {
+    /// Convert a classic [`Histogram`] to the coarsest schema that keeps its buckets apart
+    ///
+    /// See [`from_classic_with_schema()`](Self::from_classic_with_schema)
+    pub fn from_classic(histogram: &Histogram<'_>) -> Self {
+        let bounds: Vec<f64> = histogram
+            .buckets
+            .iter()
+            .map(|bucket| bucket.upper_bound)
+            .filter(|bound| bound.is_finite() && *bound > 0.0)
+            .collect();
+
+        let schema = (MIN_SCHEMA..=MAX_SCHEMA)
+            .find(|&schema| {
+                let mut indexes: Vec<_> = bounds.iter().map(|&b| index(b, schema)).collect();
+                indexes.sort_unstable();
+
+                indexes.windows(2).all(|pair| pair[0] != pair[1])
+            })
+            .unwrap_or(MAX_SCHEMA);
+
+        convert(histogram, schema)
+    }
+
+    /// Convert a classic [`Histogram`] to native buckets of `schema`
+    ///
+    /// The observations of each classic bucket are placed in the native bucket containing its
+    /// upper bound.  Observations at or below zero are placed in the zero bucket and
+    /// observations above the largest finite bound in the next native bucket, so the
+    /// conversion is approximate unless the classic boundaries are native boundaries.  When no
+    /// finite bound is positive the observations above the largest bound are only counted in
+    /// `count`.
+    ///
+    /// Returns `None` if `schema` is not from -4 to 8.
+    pub fn from_classic_with_schema(histogram: &Histogram<'_>, schema: i32) -> Option<Self> {
+        is_valid_schema(schema).then(|| convert(histogram, schema))
+    }
+
+    /// Convert delta-encoded bucket counts to absolute counts
+    pub fn expand_deltas(deltas: &[f64]) -> Vec<f64> {
+        deltas
+            .iter()
+            .scan(0.0, |count, delta| {
+                *count += delta;
+
+                Some(*count)
+            })
+            .collect()
+    }
+
+    /// The upper bound of the bucket at `index` in `schema`
+    ///
+    /// The boundary between buckets `index` and `index + 1` is `2^((index + 1) * 2^-schema)`.
+    /// Returns `None` if `schema` is not from -4 to 8.
+    pub fn upper_bound(index: i32, schema: i32) -> Option<f64> {
+        is_valid_schema(schema).then(|| bound(index, schema))
+    }
+
+    /// Convert to a classic [`Histogram`] with a bucket at the upper bound of each native bucket
+    ///
+    /// Negative native buckets include their lower bound, so observations exactly on a negative
+    /// boundary are counted in the next classic bucket.
+    pub fn to_classic(&self) -> Histogram<'static> {
+        let mut cumulative = 0.0;
+
+        let mut buckets: Vec<Bucket> = self
+            .buckets()
+            .map(|bucket| {
+                cumulative += bucket.count;
+
+                Bucket::new(bucket.upper, cumulative)
+            })
+            .collect();
+
+        buckets.push(Bucket::new(f64::INFINITY, self.count));
+
+        Histogram {
+            buckets,
+            count: Some(self.count),
+            sum: Some(self.sum),
+            ..Histogram::new(Labels::new())
+        }
+    }
+
+    /// The buckets ordered by boundary from the most negative to the most positive
+    ///
+    /// The zero bucket is included when it has a width or observations.
+    pub fn buckets(&self) -> impl Iterator<Item = NativeBucket> + '_ {
+        let negative: Vec<_> = self.negative().collect();
+
+        let zero = (self.zero_threshold > 0.0 || self.zero_count > 0.0).then_some(NativeBucket {
+            lower: -self.zero_threshold,
+            upper: self.zero_threshold,
+            count: self.zero_count,
+        });
+
+        negative
+            .into_iter()
+            .rev()
+            .chain(zero)
+            .chain(self.positive())
+    }
+
+    /// The negative buckets ordered from the zero bucket outward
+    pub fn negative(&self) -> impl Iterator<Item = NativeBucket> + '_ {
+        indexes(self.spans(&self.negative_spans))
+            .zip(&self.negative_buckets)
+            .map(|(index, &count)| NativeBucket {
+                lower: -bound(index, self.schema),
+                upper: -bound(index.saturating_sub(1), self.schema),
+                count,
+            })
+    }
+
+    /// The positive buckets ordered from the zero bucket outward
+    pub fn positive(&self) -> impl Iterator<Item = NativeBucket> + '_ {
+        indexes(self.spans(&self.positive_spans))
+            .zip(&self.positive_buckets)
+            .map(|(index, &count)| NativeBucket {
+                lower: bound(index.saturating_sub(1), self.schema),
+                upper: bound(index, self.schema),
+                count,
+            })
+    }
+
+    /// `spans`, or no spans if the schema is invalid
+    fn spans<'s>(&self, spans: &'s [Span]) -> &'s [Span] {
+        if is_valid_schema(self.schema) {
+            spans
+        } else {
+            &[]
+        }
+    }
+}
+
+impl fmt::Display for NativeHistogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let field = |f: &mut fmt::Formatter<'_>, name: &str, value: f64| {
+            write!(f, "{name}:")?;
+            fmt_number(value, f)
+        };
+
+        f.write_str("{")?;
+        field(f, "count", self.count)?;
+        f.write_str(",")?;
+        field(f, "sum", self.sum)?;
+        write!(f, ",schema:{}", self.schema)?;
+        f.write_str(",")?;
+        field(f, "zero_threshold", self.zero_threshold)?;
+        f.write_str(",")?;
+        field(f, "zero_count", self.zero_count)?;
+
+        for (sign, spans, buckets) in [
+            ("negative", &self.negative_spans, &self.negative_buckets),
+            ("positive", &self.positive_spans, &self.positive_buckets),
+        ] {
+            if spans.is_empty() {
+                continue;
+            }
+
+            write!(f, ",{sign}_spans:[")?;
+
+            for (i, span) in spans.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(",")?;
+                }
+
+                write!(f, "{}:{}", span.offset, span.length)?;
+            }
+
+            let integral = buckets.iter().all(|count| count.fract() == 0.0);
+
+            if integral {
+                write!(f, "],{sign}_deltas:[")?;
+            } else {
+                write!(f, "],{sign}_buckets:[")?;
+            }
+
+            let mut previous = 0.0;
+
+            for (i, &count) in buckets.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(",")?;
+                }
+
+                if integral {
+                    fmt_number(count - previous, f)?;
+                    previous = count;
+                } else {
+                    fmt_number(count, f)?;
+                }
+            }
+
+            f.write_str("]")?;
+        }
+
+        f.write_str("}")
+    }
+}
+
+/// Returns `true` if `schema` is a standard exponential schema
+pub(crate) fn is_valid_schema(schema: i32) -> bool {
+    (MIN_SCHEMA..=MAX_SCHEMA).contains(&schema)
+}
+
+/// Convert a classic [`Histogram`] to native buckets of valid `schema`
+fn convert(histogram: &Histogram<'_>, schema: i32) -> NativeHistogram {
+    let mut buckets = histogram.buckets.clone();
+    buckets.sort_by(|a, b| a.upper_bound.total_cmp(&b.upper_bound));
+
+    let mut native = NativeHistogram {
+        schema,
+        sum: histogram.sum.unwrap_or_default(),
+        ..NativeHistogram::default()
+    };
+
+    let mut counts: Vec<(i32, f64)> = vec![];
+    let mut previous = 0.0;
+    let mut last = None;
+
+    for bucket in &buckets {
+        let count = (bucket.count - previous).max(0.0);
+        previous = previous.max(bucket.count);
+
+        let index = if bucket.upper_bound <= 0.0 {
+            native.zero_count += count;
+            continue;
+        } else if bucket.upper_bound.is_finite() {
+            index(bucket.upper_bound, schema)
+        } else if let Some(last) = last {
+            last + 1
+        } else {
+            // Without a positive bound there is no native bucket above the classic buckets
+            continue;
+        };
+
+        last = Some(index);
+
+        match counts.last_mut() {
+            Some((last, total)) if *last == index => *total += count,
+            _ => counts.push((index, count)),
+        }
+    }
+
+    counts.retain(|&(_, count)| count > 0.0);
+
+    native.positive_spans = spans(counts.iter().map(|&(index, _)| index));
+    native.positive_buckets = counts.iter().map(|&(_, count)| count).collect();
+    native.count = histogram.count.unwrap_or(previous);
+
+    native
+}
+
+/// The upper bound of the bucket at `index` like Prometheus
+///
+/// `schema` must be valid.
+fn bound(index: i32, schema: i32) -> f64 {
+    debug_assert!(is_valid_schema(schema), "invalid schema {schema}");
+
+    if schema < 0 {
+        let exp = i64::from(index) << -schema;
+
+        if exp == 1024 {
+            return f64::MAX;
+        }
+
+        return 2f64.powi(exp.clamp(-2048, 2048) as i32);
+    }
+
+    let frac_index = index & ((1 << schema) - 1);
+    let frac = 2f64.powf(f64::from(frac_index) / f64::from(1 << schema)) / 2.0;
+    let exp = (index >> schema) + 1;
+
+    if frac == 0.5 && exp == 1025 {
+        return f64::MAX;
+    }
+
+    frac * 2f64.powi(exp)
+}
+
+/// The index of the bucket containing positive `value`
+fn index(value: f64, schema: i32) -> i32 {
+    let estimate = (value.log2() * 2f64.powi(schema)).ceil();
+    let mut index = estimate.clamp(f64::from(i32::MIN / 2), f64::from(i32::MAX / 2)) as i32;
+
+    while bound(index - 1, schema) >= value {
+        index -= 1;
+    }
+
+    while bound(index, schema) < value {
+        index += 1;
+    }
+
+    index
+}
+
+/// Build spans covering sorted bucket `indexes`
+fn spans(indexes: impl Iterator<Item = i32>) -> Vec<Span> {
+    let mut spans: Vec<Span> = vec![];
+    let mut next = 0;
+
+    for index in indexes {
+        match spans.last_mut() {
+            Some(span) if index == next => span.length += 1,
+            _ => spans.push(Span::new(index - next, 1)),
+        }
+
+        next = index + 1;
+    }
+
+    spans
+}
+
+/// The bucket indexes covered by `spans`
+///
+/// Indexes beyond the `i32` range are cut off.
+fn indexes(spans: &[Span]) -> impl Iterator<Item = i32> + '_ {
+    let mut next: i32 = 0;
+
+    spans.iter().flat_map(move |span| {
+        let start = next.saturating_add(span.offset);
+        next = start.saturating_add_unsigned(span.length);
+
+        start..next
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use assert_float_eq::assert_float_absolute_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0, 0, 1.0)]
+    #[case(1, 0, 2.0)]
+    #[case(-1, 0, 0.5)]
+    #[case(0, 1, 1.0)]
+    #[case(1, 1, std::f64::consts::SQRT_2)]
+    #[case(2, 1, 2.0)]
+    #[case(-1, 1, std::f64::consts::FRAC_1_SQRT_2)]
+    #[case(1, -1, 4.0)]
+    #[case(-1, -1, 0.25)]
+    #[case(4, 3, std::f64::consts::SQRT_2)]
+    fn bound(#[case] index: i32, #[case] schema: i32, #[case] expected: f64) {
+        let actual = super::bound(index, schema);
+
+        assert!(
+            (expected - actual).abs() <= f64::EPSILON * expected,
+            "bound({index}, {schema}) = {actual}, expected {expected}"
+        );
+    }
+
+    #[rstest]
+    #[case(1.0, 0, 0)]
+    #[case(1.5, 0, 1)]
+    #[case(2.0, 0, 1)]
+    #[case(0.5, 0, -1)]
+    #[case(0.75, 0, 0)]
+    #[case(std::f64::consts::SQRT_2, 1, 1)]
+    #[case(1.5, 1, 2)]
+    #[case(4.0, -1, 1)]
+    #[case(5.0, -1, 2)]
+    fn index(#[case] value: f64, #[case] schema: i32, #[case] expected: i32) {
+        assert_eq!(expected, super::index(value, schema));
+    }
+
+    #[test]
+    fn expand_deltas() {
+        assert_eq!(
+            vec![2.0, 3.0, 0.0],
+            NativeHistogram::expand_deltas(&[2.0, 1.0, -3.0])
+        );
+    }
+
+    #[test]
+    fn to_classic() {
+        let native = NativeHistogram {
+            count: 7.0,
+            sum: 12.0,
+            schema: 0,
+            zero_threshold: 0.001,
+            zero_count: 1.0,
+            positive_spans: vec![Span::new(0, 2), Span::new(1, 1)],
+            positive_buckets: vec![2.0, 3.0, 1.0],
+            ..NativeHistogram::default()
+        };
+
+        let classic = native.to_classic();
+
+        assert_eq!(
+            vec![
+                Bucket::new(0.001, 1.0),
+                Bucket::new(1.0, 3.0),
+                Bucket::new(2.0, 6.0),
+                Bucket::new(8.0, 7.0),
+                Bucket::new(f64::INFINITY, 7.0),
+            ],
+            classic.buckets
+        );
+        assert_eq!(Some(7.0), classic.count);
+        assert_eq!(Some(12.0), classic.sum);
+        assert_float_absolute_eq!(
+            crate::bucket_quantile(0.5, &classic.buckets),
+            1.0 + 1.0 * (0.5 / 3.0)
+        );
+    }
+
+    #[test]
+    fn from_classic() {
+        let classic = Histogram {
+            buckets: vec![
+                Bucket::new(1.0, 1.0),
+                Bucket::new(2.0, 3.0),
+                Bucket::new(4.0, 3.0),
+                Bucket::new(8.0, 6.0),
+                Bucket::new(f64::INFINITY, 7.0),
+            ],
+            count: Some(7.0),
+            sum: Some(20.0),
+            ..Histogram::new(Labels::new())
+        };
+
+        let native = NativeHistogram::from_classic(&classic);
+
+        assert_eq!(
+            NativeHistogram {
+                count: 7.0,
+                sum: 20.0,
+                schema: 0,
+                positive_spans: vec![Span::new(0, 2), Span::new(1, 2)],
+                positive_buckets: vec![1.0, 2.0, 3.0, 1.0],
+                ..NativeHistogram::default()
+            },
+            native
+        );
+    }
+
+    #[test]
+    fn from_classic_round_trip() {
+        let native = NativeHistogram {
+            count: 6.0,
+            sum: 3.0,
+            schema: 2,
+            positive_spans: vec![Span::new(-3, 3), Span::new(2, 1)],
+            positive_buckets: vec![1.0, 2.0, 1.0, 2.0],
+            ..NativeHistogram::default()
+        };
+
+        let classic = native.to_classic();
+
+        assert_eq!(
+            Some(native),
+            NativeHistogram::from_classic_with_schema(&classic, 2)
+        );
+    }
+
+    #[test]
+    fn from_classic_non_positive() {
+        let classic = Histogram {
+            buckets: vec![
+                Bucket::new(-1.0, 1.0),
+                Bucket::new(0.0, 2.0),
+                Bucket::new(f64::INFINITY, 5.0),
+            ],
+            count: Some(5.0),
+            sum: Some(20.0),
+            ..Histogram::new(Labels::new())
+        };
+
+        assert_eq!(
+            NativeHistogram {
+                count: 5.0,
+                sum: 20.0,
+                schema: MIN_SCHEMA,
+                zero_count: 2.0,
+                ..NativeHistogram::default()
+            },
+            NativeHistogram::from_classic(&classic)
+        );
+    }
+
+    #[test]
+    fn invalid_schema() {
+        let classic = Histogram::new(Labels::new());
+
+        assert_eq!(None, NativeHistogram::from_classic_with_schema(&classic, 9));
+        assert_eq!(
+            None,
+            NativeHistogram::from_classic_with_schema(&classic, -5)
+        );
+        assert_eq!(None, NativeHistogram::upper_bound(0, 40));
+        assert_eq!(Some(2.0), NativeHistogram::upper_bound(1, 0));
+
+        let histogram = NativeHistogram {
+            schema: 40,
+            positive_spans: vec![Span::new(0, 1)],
+            positive_buckets: vec![1.0],
+            ..NativeHistogram::default()
+        };
+
+        assert_eq!(0, histogram.buckets().count());
+    }
+
+    #[test]
+    fn buckets_extreme_spans() {
+        let histogram = NativeHistogram {
+            positive_spans: vec![Span::new(i32::MAX, 2), Span::new(i32::MIN, 1)],
+            positive_buckets: vec![1.0, 1.0, 1.0],
+            negative_spans: vec![Span::new(i32::MIN, 1)],
+            negative_buckets: vec![1.0],
+            ..NativeHistogram::default()
+        };
+
+        assert_eq!(2, histogram.buckets().count());
+    }
+
+    #[test]
+    fn buckets() {
+        let histogram = NativeHistogram {
+            count: 9.0,
+            sum: 10.0,
+            schema: 0,
+            zero_threshold: 0.001,
+            zero_count: 1.0,
+            positive_spans: vec![Span::new(0, 2), Span::new(1, 1)],
+            positive_buckets: vec![2.0, 3.0, 1.0],
+            negative_spans: vec![Span::new(1, 1)],
+            negative_buckets: vec![2.0],
+        };
+
+        assert_eq!(
+            vec![
+                NativeBucket {
+                    lower: -2.0,
+                    upper: -1.0,
+                    count: 2.0
+                },
+                NativeBucket {
+                    lower: -0.001,
+                    upper: 0.001,
+                    count: 1.0
+                },
+                NativeBucket {
+                    lower: 0.5,
+                    upper: 1.0,
+                    count: 2.0
+                },
+                NativeBucket {
+                    lower: 1.0,
+                    upper: 2.0,
+                    count: 3.0
+                },
+                NativeBucket {
+                    lower: 4.0,
+                    upper: 8.0,
+                    count: 1.0
+                },
+            ],
+            histogram.buckets().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn display() {
+        let histogram = NativeHistogram {
+            count: 12.0,
+            sum: 100.5,
+            schema: 3,
+            zero_threshold: 1e-128,
+            zero_count: 0.0,
+            positive_spans: vec![Span::new(0, 2)],
+            positive_buckets: vec![2.0, 3.0],
+            ..NativeHistogram::default()
+        };
+
+        assert_eq!(
+            "{count:12,sum:100.5,schema:3,zero_threshold:1e-128,zero_count:0,positive_spans:[0:2],positive_deltas:[2,1]}",
+            histogram.to_string()
+        );
+
+        let gauge = NativeHistogram {
+            negative_spans: vec![Span::new(-1, 1)],
+            negative_buckets: vec![0.5],
+            ..NativeHistogram::default()
+        };
+
+        assert_eq!(
+            "{count:0,sum:0,schema:0,zero_threshold:0,zero_count:0,negative_spans:[-1:1],negative_buckets:[0.5]}",
+            gauge.to_string()
+        );
+    }
+}
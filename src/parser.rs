@@ -3,6 +3,15 @@
 //! Use [`prometheus()`] and [`openmetrics()`] to parse an entire exposition (set of metrics).  These
 //! are best used when you can fit the entire parsed exposition in memory.
 //!
+//! With the `rayon` feature, use `par_prometheus()` and `par_openmetrics()` to parse a large
+//! exposition on multiple threads.  The result is identical to parsing with `prometheus()` or
+//! `openmetrics()`.
+//!
+//! Use [`openmetrics2()`] to parse the OpenMetrics 2.0 draft format, with `st@` start timestamps,
+//! sample timestamps, and quoted metric and label names.  Both versions can be parsed side by side
+//! while exporters migrate, and [`Format::from_content_type()`] picks one from the HTTP
+//! `Content-Type` header.
+//!
 //! Use [`family()`] to parse a chunk of an exposition.  This is best used when you are streaming
 //! an exposition.  If the result is an error you will need to fill the input buffer and retry, and
 //! check for EOF with either `eof_marker()` (OpenMetrics) or [`eof()`](nom::combinator::eof())
@@ -10,28 +19,94 @@
 //!
 //! Use [`eof_marker()`] to detect the end of an OpenMetrics-format exposition you are consuming
 //! with `family()`.
+//!
+//! Use [`visit_prometheus()`] and [`visit_openmetrics()`] to pass each descriptor and sample of
+//! an exposition to a [`Visitor`] instead of collecting them into [`Family`]s.  This is best used
+//! when you transform the exposition immediately.
+//!
+//! Use [`prometheus_filtered()`] and [`openmetrics_filtered()`] to parse only the families you
+//! are interested in.  Samples of other families are skipped without parsing their labels or
+//! values.
+//!
+//! Use [`prometheus_metadata()`] and [`openmetrics_metadata()`] to collect only the descriptors
+//! and number of series of each family.
+//!
+//! Use [`selector()`] to parse a PromQL-style selector for
+//! [`Exposition::select()`](crate::Exposition::select()).
+//!
+//! Use an [`ExpositionParser`] to parse expositions repeatedly, such as when scraping the same
+//! target, reusing the allocations from previous expositions.  Use
+//! [`ExpositionParser::with_unescaping()`] to recover UTF-8 names that a target escaped with an
+//! [`Escaping`](crate::Escaping) scheme.
+//!
+//! The other parsing functions return names as written.  Collect their families into an
+//! [`Exposition`](crate::Exposition) and call
+//! [`Exposition::unescape_names()`](crate::Exposition::unescape_names) to unescape them.  The
+//! predicates of the filtered parsers are called with the escaped names.
+//!
+//! Use [`syntax_tree()`] to parse an exposition without discarding comments, blank lines, or the
+//! original text of each line.  This is best used by tools that rewrite expositions.
+//!
+//! Each entry point has a `_with_limits` variant such as [`prometheus_with_limits()`] that stops
+//! with an [`Error::Limit`] when the input exceeds the given [`Limits`].  Use these when parsing
+//! expositions from targets you do not control.
 
-mod label;
+mod error;
+mod exposition;
+mod fast;
+mod filter;
+pub(crate) mod label;
+mod limits;
+mod metadata;
 mod metric_descriptor;
-mod metric_name;
+pub(crate) mod metric_name;
+mod native_histogram;
 mod number;
+mod openmetrics2;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod selector;
 mod string;
+mod syntax;
+mod visit;
 
-use crate::{Family, Sample};
-use label::labels;
+pub use error::Error;
+pub use exposition::{ExpositionParser, Format};
+pub use filter::{
+    openmetrics_filtered, openmetrics_filtered_with_limits, prometheus_filtered,
+    prometheus_filtered_with_limits, NameFilter,
+};
+pub use metadata::{
+    openmetrics_metadata, openmetrics_metadata_with_limits, prometheus_metadata,
+    prometheus_metadata_with_limits,
+};
+#[cfg(feature = "rayon")]
+pub use parallel::{
+    par_openmetrics, par_openmetrics_with_limits, par_prometheus, par_prometheus_with_limits,
+};
+pub use selector::selector;
+
+use crate::{
+    syntax::LineKind, Family, Label, Limit, Limits, NativeHistogram, Sample, SyntaxTree, Visitor,
+};
+use label::labels_into;
 use metric_descriptor::metric_descriptor;
 use metric_name::metric_name;
+use native_histogram::native_histogram;
 use nom::{
+    branch::alt,
     bytes::complete::tag,
     character::complete::char,
     combinator::{all_consuming, cut, eof, map, opt},
     error::context,
-    multi::{many0, many1},
+    multi::{many0, many1, many_till},
     sequence::{pair, preceded, terminated},
     IResult, Parser,
 };
 use nom_language::error::VerboseError;
 use number::number;
+use std::{borrow::Cow, cell::Cell};
+use visit::Families;
 
 /// An OpenMetrics EOF marker
 pub fn eof_marker(input: &str) -> IResult<&str, (), VerboseError<&str>> {
@@ -42,7 +117,47 @@ pub fn eof_marker(input: &str) -> IResult<&str, (), VerboseError<&str>> {
 ///
 /// This must be terminated with `# EOF`.  See also [`prometheus`]
 pub fn openmetrics(input: &str) -> IResult<&str, Vec<Family<'_>>, VerboseError<&str>> {
-    context("openmetrics", terminated(set, eof_marker)).parse(input)
+    openmetrics_with_limits(input, &Limits::default()).map_err(verbose)
+}
+
+/// Parse an OpenMetrics-format exposition within `limits`
+///
+/// See also [`openmetrics`]
+pub fn openmetrics_with_limits<'a>(
+    input: &'a str,
+    limits: &Limits,
+) -> IResult<&'a str, Vec<Family<'a>>, Error<&'a str>> {
+    let mut families = Families::default();
+
+    let (rest, ()) = visit_openmetrics(input, limits, &mut families)?;
+
+    Ok((rest, families.into_families()))
+}
+
+/// Parse an OpenMetrics 2.0 draft exposition
+///
+/// Samples may have a timestamp and a `st@` start timestamp after the value, and metric and label
+/// names may be quoted.  This must be terminated with `# EOF`.  See also [`openmetrics`]
+///
+/// The start timestamp replaces `_created` samples, which are still accepted as ordinary samples
+/// for exporters migrating from OpenMetrics 1.0.  Typed views such as
+/// [`Family::counters()`](crate::Family::counters) prefer a `_created` sample over `st@`.
+pub fn openmetrics2(input: &str) -> IResult<&str, Vec<Family<'_>>, VerboseError<&str>> {
+    openmetrics2_with_limits(input, &Limits::default()).map_err(verbose)
+}
+
+/// Parse an OpenMetrics 2.0 draft exposition within `limits`
+///
+/// See also [`openmetrics2`]
+pub fn openmetrics2_with_limits<'a>(
+    input: &'a str,
+    limits: &Limits,
+) -> IResult<&'a str, Vec<Family<'a>>, Error<&'a str>> {
+    let mut families = Families::default();
+
+    let (rest, ()) = visit_openmetrics2(input, limits, &mut families)?;
+
+    Ok((rest, families.into_families()))
 }
 
 /// Parse a [`Family`] of metrics
@@ -57,36 +172,315 @@ pub fn family(input: &str) -> IResult<&str, Family<'_>, VerboseError<&str>> {
     .parse(input)
 }
 
+/// Parse a [`Family`] of metrics within `limits`
+///
+/// The samples limit applies to this family alone.  See also [`family`]
+pub fn family_with_limits<'a>(
+    input: &'a str,
+    limits: &Limits,
+) -> IResult<&'a str, Family<'a>, Error<&'a str>> {
+    family_within(input, limits, 0)
+}
+
 /// Parse a Prometheus-format exposition
 ///
 /// This format is more likely to match prometheus scrape targets
 pub fn prometheus(input: &str) -> IResult<&str, Vec<Family<'_>>, VerboseError<&str>> {
-    context("prometheus", all_consuming(terminated(set, cut(eof)))).parse(input)
+    prometheus_with_limits(input, &Limits::default()).map_err(verbose)
+}
+
+/// Parse a Prometheus-format exposition within `limits`
+///
+/// See also [`prometheus`]
+pub fn prometheus_with_limits<'a>(
+    input: &'a str,
+    limits: &Limits,
+) -> IResult<&'a str, Vec<Family<'a>>, Error<&'a str>> {
+    let mut families = Families::default();
+
+    let (rest, ()) = visit_prometheus(input, limits, &mut families)?;
+
+    Ok((rest, families.into_families()))
 }
 
 /// Parse a single metric sample
 pub(crate) fn sample(input: &str) -> IResult<&str, Sample<'_>, VerboseError<&str>> {
-    context(
-        "sample",
-        map(
+    sample_into(input, &mut vec![], Version::V1, &Limits::default()).map_err(verbose)
+}
+
+/// Parse a single metric sample within `limits`, collecting its labels in the `labels` buffer
+///
+/// If the sample has labels the buffer is moved into the sample, leaving `labels` empty.  Label
+/// limits are enforced as labels are parsed, before their values are allocated.
+pub(crate) fn sample_into<'a>(
+    input: &'a str,
+    labels: &mut Vec<Label<'a>>,
+    version: Version,
+    limits: &Limits,
+) -> IResult<&'a str, Sample<'a>, Error<&'a str>> {
+    if let Some(parsed) = fast::sample(input, labels, limits) {
+        return Ok(parsed);
+    }
+
+    sample_grammar(input, labels, version, limits)
+}
+
+/// Parse a single metric sample with the complete grammar, bypassing the fast path
+fn sample_grammar<'a>(
+    input: &'a str,
+    labels: &mut Vec<Label<'a>>,
+    version: Version,
+    limits: &Limits,
+) -> IResult<&'a str, Sample<'a>, Error<&'a str>> {
+    labels.clear();
+
+    if version == Version::V2 {
+        let (rest, parts) = context(
+            "sample",
             terminated(
-                (metric_name, opt(labels), preceded(char(' '), metric_value)),
+                |input| openmetrics2::sample(input, labels, limits),
                 char('\n'),
             ),
-            |(name, labels, number)| {
-                if let Some(labels) = labels {
-                    Sample::with_labels(name, number, labels)
-                } else {
-                    Sample::new(name, number)
-                }
-            },
+        )
+        .parse(input)?;
+
+        let mut sample = sample_with_value(parts.name, std::mem::take(labels), parts.value);
+
+        if let Some(timestamp) = parts.timestamp {
+            sample = sample.with_timestamp(timestamp);
+        }
+
+        if let Some(start_timestamp) = parts.start_timestamp {
+            sample = sample.with_start_timestamp(start_timestamp);
+        }
+
+        return Ok((rest, sample));
+    }
+
+    let (rest, (name, _, value)) = context(
+        "sample",
+        terminated(
+            (
+                |input| lift(metric_name(input)),
+                opt(|input| labels_into(input, labels, limits)),
+                preceded(char(' '), |input| lift(sample_value(input))),
+            ),
+            char('\n'),
         ),
     )
+    .parse(input)?;
+
+    Ok((rest, sample_with_value(name, std::mem::take(labels), value)))
+}
+
+/// Single sample line parsers for comparing the fast path with the complete grammar
+///
+/// Only available with the `bench` feature, which is not covered by semver.
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod bench {
+    use super::{Error, Limits, Sample, Version};
+    use nom::IResult;
+
+    /// Parse a Prometheus sample line, using the fast path when possible
+    pub fn sample(input: &str) -> IResult<&str, Sample<'_>, Error<&str>> {
+        super::sample_into(input, &mut vec![], Version::V1, &Limits::default())
+    }
+
+    /// Parse a Prometheus sample line with the complete grammar
+    pub fn sample_grammar(input: &str) -> IResult<&str, Sample<'_>, Error<&str>> {
+        super::sample_grammar(input, &mut vec![], Version::V1, &Limits::default())
+    }
+}
+
+/// Create a [`Sample`] from its parsed parts
+fn sample_with_value<'a>(
+    name: impl Into<Cow<'a, str>>,
+    labels: Vec<Label<'a>>,
+    value: SampleValue,
+) -> Sample<'a> {
+    match value {
+        SampleValue::Number(number) => Sample::with_labels(name, number, labels),
+        SampleValue::NativeHistogram(histogram) => {
+            Sample::with_labels(name, histogram.count, labels).with_native_histogram(histogram)
+        }
+    }
+}
+
+/// Parse an exposition into a lossless [`SyntaxTree`]
+///
+/// Comments, blank lines, and an `# EOF` marker are accepted anywhere.
+pub fn syntax_tree(input: &str) -> IResult<&str, SyntaxTree<'_>, VerboseError<&str>> {
+    context(
+        "syntax tree",
+        map(many_till(syntax::line, eof), |(lines, _)| {
+            SyntaxTree::new(lines)
+        }),
+    )
     .parse(input)
 }
 
-fn set(input: &str) -> IResult<&str, Vec<Family<'_>>, VerboseError<&str>> {
-    context("set", many0(family)).parse(input)
+/// Parse an exposition into a lossless [`SyntaxTree`] within `limits`
+///
+/// A family starts at each descriptor following a sample, as in
+/// [`SyntaxTree::into_families()`].  See also [`syntax_tree`]
+pub fn syntax_tree_with_limits<'a>(
+    input: &'a str,
+    limits: &Limits,
+) -> IResult<&'a str, SyntaxTree<'a>, Error<&'a str>> {
+    let samples = Cell::new(0);
+    let families = Cell::new(0);
+    let in_samples = Cell::new(false);
+
+    let line = |input| {
+        let (rest, line) = syntax::line_with_limits(input, limits)?;
+
+        match line.kind() {
+            LineKind::Descriptor(_) if in_samples.get() || families.get() == 0 => {
+                in_samples.set(false);
+                families.set(families.get() + 1);
+            }
+            LineKind::Sample(_) => {
+                if families.get() == 0 {
+                    families.set(1);
+                }
+
+                in_samples.set(true);
+                samples.set(samples.get() + 1);
+            }
+            _ => (),
+        }
+
+        if limits::exceeds(limits.max_families, families.get()) {
+            return Err(Error::limit(input, Limit::Families));
+        }
+
+        if limits::exceeds(limits.max_samples, samples.get()) {
+            return Err(Error::limit(input, Limit::Samples));
+        }
+
+        Ok((rest, line))
+    };
+
+    let mut syntax_tree = context(
+        "syntax tree",
+        map(many_till(line, eof), |(lines, _)| SyntaxTree::new(lines)),
+    );
+
+    syntax_tree.parse(input)
+}
+
+/// Parse a [`Family`] within `limits` when `samples` have already been parsed
+fn family_within<'a>(
+    input: &'a str,
+    limits: &Limits,
+    samples: usize,
+) -> IResult<&'a str, Family<'a>, Error<&'a str>> {
+    let samples = Cell::new(samples);
+    let mut labels = vec![];
+
+    let sample = |input| {
+        let (rest, sample) = limits::sample(input, limits, &mut labels, Version::V1)?;
+
+        samples.set(samples.get() + 1);
+
+        if limits::exceeds(limits.max_samples, samples.get()) {
+            return Err(Error::limit(input, Limit::Samples));
+        }
+
+        Ok((rest, sample))
+    };
+
+    let mut family = context(
+        "family",
+        map(
+            pair(
+                many0(|input| limits::descriptor(input, limits, Version::V1)),
+                many1(sample),
+            ),
+            |(descriptors, samples)| Family::new(descriptors, samples),
+        ),
+    );
+
+    family.parse(input)
+}
+
+/// Convert the result of a parser without limits for use in a parser that enforces them
+fn lift<I, O>(result: IResult<I, O, VerboseError<I>>) -> IResult<I, O, Error<I>> {
+    result.map_err(|e| e.map(Error::Parse))
+}
+
+/// Convert an error from a parser without limits
+fn verbose<I>(error: nom::Err<Error<I>>) -> nom::Err<VerboseError<I>> {
+    error.map(|error| match error {
+        Error::Parse(error) => error,
+        Error::Limit { .. } => unreachable!("no limits were set"),
+    })
+}
+
+/// Parse an OpenMetrics-format exposition within `limits`, passing its contents to `visitor`
+///
+/// Use [`Limits::default()`] to parse without limits.  See also [`openmetrics`]
+pub fn visit_openmetrics<'a, V: Visitor<'a>>(
+    input: &'a str,
+    limits: &Limits,
+    visitor: &mut V,
+) -> IResult<&'a str, (), Error<&'a str>> {
+    visit_openmetrics_version(input, limits, Version::V1, visitor)
+}
+
+/// Parse an OpenMetrics 2.0 draft exposition within `limits`, passing its contents to `visitor`
+///
+/// Use [`Limits::default()`] to parse without limits.  See also [`openmetrics2`]
+pub fn visit_openmetrics2<'a, V: Visitor<'a>>(
+    input: &'a str,
+    limits: &Limits,
+    visitor: &mut V,
+) -> IResult<&'a str, (), Error<&'a str>> {
+    visit_openmetrics_version(input, limits, Version::V2, visitor)
+}
+
+/// Parse an OpenMetrics exposition of `version` within `limits`, passing its contents to `visitor`
+fn visit_openmetrics_version<'a, V: Visitor<'a>>(
+    input: &'a str,
+    limits: &Limits,
+    version: Version,
+    visitor: &mut V,
+) -> IResult<&'a str, (), Error<&'a str>> {
+    let (rest, ()) = context(
+        "openmetrics",
+        terminated(
+            |input| visit::set(input, limits, version, visitor),
+            |input| eof_marker(input).map_err(|e| e.map(Error::Parse)),
+        ),
+    )
+    .parse(input)?;
+
+    visitor.on_eof();
+
+    Ok((rest, ()))
+}
+
+/// Parse a Prometheus-format exposition within `limits`, passing its contents to `visitor`
+///
+/// Use [`Limits::default()`] to parse without limits.  See also [`prometheus`]
+pub fn visit_prometheus<'a, V: Visitor<'a>>(
+    input: &'a str,
+    limits: &Limits,
+    visitor: &mut V,
+) -> IResult<&'a str, (), Error<&'a str>> {
+    let (rest, ()) = context(
+        "prometheus",
+        all_consuming(terminated(
+            |input| visit::set(input, limits, Version::V1, visitor),
+            cut(eof),
+        )),
+    )
+    .parse(input)?;
+
+    visitor.on_eof();
+
+    Ok((rest, ()))
 }
 
 /// Matches a metric value
@@ -94,6 +488,29 @@ fn metric_value(input: &str) -> IResult<&str, f64, VerboseError<&str>> {
     context("metric value", number).parse(input)
 }
 
+/// The version of the exposition syntax
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Version {
+    /// The Prometheus text format and OpenMetrics 1.0
+    V1,
+    /// The OpenMetrics 2.0 draft
+    V2,
+}
+
+/// The value of a sample
+enum SampleValue {
+    Number(f64),
+    NativeHistogram(NativeHistogram),
+}
+
+fn sample_value(input: &str) -> IResult<&str, SampleValue, VerboseError<&str>> {
+    alt((
+        map(native_histogram, SampleValue::NativeHistogram),
+        map(metric_value, SampleValue::Number),
+    ))
+    .parse(input)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -169,6 +586,211 @@ mod test {
         assert!(rest.is_empty());
     }
 
+    #[test]
+    fn sample_native_histogram() {
+        let input = "# TYPE latency histogram\nlatency{path=\"/\"} {count:3,sum:1.5,schema:0,zero_threshold:0,zero_count:1,positive_spans:[0:1],positive_deltas:[2]}\n# EOF\n";
+
+        let (_, families) = parse(super::openmetrics, input);
+
+        let sample = &families[0].samples[0];
+        let histogram = sample.native_histogram().unwrap();
+
+        assert_eq!(3.0, sample.number());
+        assert_eq!(vec![2.0], histogram.positive_buckets);
+        assert_eq!(input.lines().nth(1).unwrap(), sample.to_string(),);
+    }
+
+    #[test]
+    fn openmetrics2() {
+        let input = "# TYPE \"http.requests\" counter
+{\"http.requests_total\",\"http.method\"=\"GET\"} 1027 1700000100 st@1700000000
+{\"http.requests_total\",\"http.method\"=\"POST\"} 3 st@1700000050.5
+# TYPE up gauge
+up 1
+# EOF
+";
+
+        let (rest, families) = parse(super::openmetrics2, input);
+
+        assert!(rest.is_empty(), "leftover: {rest:?}");
+        assert_eq!(2, families.len());
+        assert_eq!(Some("http.requests"), families[0].name());
+
+        let sample = &families[0].samples[0];
+
+        assert_eq!("http.requests_total", sample.name());
+        assert_eq!(Some("GET"), sample.labels().get("http.method"));
+        assert_eq!(Some(1700000100.0), sample.timestamp());
+        assert_eq!(Some(1700000000.0), sample.start_timestamp());
+
+        let sample = &families[0].samples[1];
+
+        assert_eq!(None, sample.timestamp());
+        assert_eq!(Some(1700000050.5), sample.start_timestamp());
+
+        let printed: Vec<_> = families
+            .iter()
+            .flat_map(|family| {
+                let descriptors = family.descriptors.iter().map(ToString::to_string);
+
+                descriptors.chain(family.samples.iter().map(ToString::to_string))
+            })
+            .collect();
+
+        assert_eq!(input.lines().take(5).collect::<Vec<_>>(), printed);
+    }
+
+    #[rstest]
+    #[case("up 1 st@1700000000\n# EOF\n")]
+    #[case("{\"my.metric\"} 1\n# EOF\n")]
+    fn openmetrics_rejects_openmetrics2(#[case] input: &str) {
+        assert!(super::openmetrics(input).is_err(), "input: {input}");
+        assert!(super::openmetrics2(input).is_ok(), "input: {input}");
+    }
+
+    const LIMITED: &str = "# TYPE up gauge\nup{job=\"prometheus\"} 1\nup{job=\"grafana\"} 0\n# TYPE requests counter\nrequests 1027\n";
+
+    fn limit<'a, T: std::fmt::Debug>(
+        result: IResult<&'a str, T, Error<&'a str>>,
+    ) -> (&'a str, Limit) {
+        match result {
+            Err(nom::Err::Failure(Error::Limit { input, limit })) => (input, limit),
+            result => unreachable!("expected limit error, got {result:?}"),
+        }
+    }
+
+    #[rstest]
+    #[case(Limits { max_samples: Some(2), ..Limits::default() }, "requests 1027\n", Limit::Samples)]
+    #[case(Limits { max_samples: Some(1), ..Limits::default() }, "up{job=\"grafana\"} 0\n# TYPE requests counter\nrequests 1027\n", Limit::Samples)]
+    #[case(Limits { max_families: Some(1), ..Limits::default() }, "# TYPE requests counter\nrequests 1027\n", Limit::Families)]
+    #[case(Limits { max_line_length: Some(20), ..Limits::default() }, "up{job=\"prometheus\"} 1\nup{job=\"grafana\"} 0\n# TYPE requests counter\nrequests 1027\n", Limit::LineLength)]
+    fn prometheus_with_limits_exceeded(
+        #[case] limits: Limits,
+        #[case] expected_input: &str,
+        #[case] expected_limit: Limit,
+    ) {
+        let (input, limit) = limit(super::prometheus_with_limits(LIMITED, &limits));
+
+        assert_eq!(expected_limit, limit);
+        assert_eq!(expected_input, input);
+    }
+
+    #[test]
+    fn prometheus_with_limits() {
+        let limits = Limits {
+            max_line_length: Some(23),
+            max_labels: Some(1),
+            max_label_name_length: Some(3),
+            max_label_value_length: Some(10),
+            max_samples: Some(3),
+            max_families: Some(2),
+            max_help_length: Some(0),
+        };
+
+        let (rest, families) = super::prometheus_with_limits(LIMITED, &limits).unwrap();
+
+        assert_eq!(2, families.len());
+        assert!(rest.is_empty(), "leftover: {rest:?}");
+
+        let Err(nom::Err::Failure(Error::Parse(_))) =
+            super::prometheus_with_limits("up 1", &limits)
+        else {
+            unreachable!("expected parse error");
+        };
+    }
+
+    #[test]
+    fn openmetrics_with_limits() {
+        let input = format!("{LIMITED}# EOF\n");
+        let limits = Limits {
+            max_families: Some(1),
+            ..Limits::default()
+        };
+
+        let (_, limit) = limit(super::openmetrics_with_limits(&input, &limits));
+
+        assert_eq!(Limit::Families, limit);
+
+        let (rest, families) = super::openmetrics_with_limits(&input, &Limits::new()).unwrap();
+
+        assert_eq!(2, families.len());
+        assert!(rest.is_empty(), "leftover: {rest:?}");
+    }
+
+    #[rstest]
+    #[case(Limits { max_samples: Some(2), ..Limits::default() }, Limit::Samples)]
+    #[case(Limits { max_families: Some(1), ..Limits::default() }, Limit::Families)]
+    #[case(Limits { max_labels: Some(0), ..Limits::default() }, Limit::Labels)]
+    fn syntax_tree_with_limits_exceeded(#[case] limits: Limits, #[case] expected: Limit) {
+        let input = format!("# comment\n{LIMITED}\n");
+
+        let (_, limit) = limit(super::syntax_tree_with_limits(&input, &limits));
+
+        assert_eq!(expected, limit);
+    }
+
+    #[test]
+    fn syntax_tree_with_limits() {
+        let input = format!("# comment\n{LIMITED}\n");
+        let limits = Limits {
+            max_samples: Some(3),
+            max_families: Some(2),
+            ..Limits::default()
+        };
+
+        let (rest, tree) = super::syntax_tree_with_limits(&input, &limits).unwrap();
+
+        assert_eq!(input, tree.to_string());
+        assert!(rest.is_empty(), "leftover: {rest:?}");
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        samples: usize,
+        families: usize,
+        eof: bool,
+    }
+
+    impl Visitor<'_> for Counter {
+        fn on_sample(&mut self, _sample: Sample<'_>) {
+            self.samples += 1;
+        }
+
+        fn on_family_end(&mut self) {
+            self.families += 1;
+        }
+
+        fn on_eof(&mut self) {
+            self.eof = true;
+        }
+    }
+
+    #[test]
+    fn visit_openmetrics() {
+        let input = format!("{LIMITED}# EOF\n");
+        let mut counter = Counter::default();
+
+        let (rest, ()) =
+            super::visit_openmetrics(&input, &Limits::default(), &mut counter).unwrap();
+
+        assert_eq!(3, counter.samples);
+        assert_eq!(2, counter.families);
+        assert!(counter.eof);
+        assert!(rest.is_empty(), "leftover: {rest:?}");
+    }
+
+    #[test]
+    fn visit_prometheus_error() {
+        let input = "up 1\n# TYPE requests counter\n";
+        let mut counter = Counter::default();
+
+        let result = super::visit_prometheus(input, &Limits::default(), &mut counter);
+
+        assert!(result.is_err());
+        assert_eq!(1, counter.samples);
+        assert!(!counter.eof);
+    }
+
     #[test]
     fn prometheus() {
         let input = "# HELP up up help text\nup{job=\"prometheus\"} 1\n";
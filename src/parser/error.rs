@@ -0,0 +1,74 @@
+use crate::Limit;
+use nom::error::{ContextError, ErrorKind, FromExternalError, ParseError};
+use nom_language::error::VerboseError;
+use std::fmt;
+
+/// An error from a parser that enforces [`Limits`](crate::Limits)
+#[derive(Debug, PartialEq)]
+pub enum Error<I> {
+    /// The input does not match the grammar
+    Parse(VerboseError<I>),
+    /// The input exceeds a limit
+    Limit {
+        /// The input where the limit was exceeded
+        input: I,
+        /// The limit that was exceeded
+        limit: Limit,
+    },
+}
+
+impl<I> Error<I> {
+    /// Create a [`Error::Limit`]
+    pub(crate) fn limit(input: I, limit: Limit) -> nom::Err<Self> {
+        nom::Err::Failure(Self::Limit { input, limit })
+    }
+}
+
+impl<I> From<VerboseError<I>> for Error<I> {
+    fn from(error: VerboseError<I>) -> Self {
+        Self::Parse(error)
+    }
+}
+
+impl<I> ParseError<I> for Error<I> {
+    fn from_error_kind(input: I, kind: ErrorKind) -> Self {
+        Self::Parse(VerboseError::from_error_kind(input, kind))
+    }
+
+    fn append(input: I, kind: ErrorKind, other: Self) -> Self {
+        match other {
+            Self::Parse(error) => Self::Parse(VerboseError::append(input, kind, error)),
+            limit => limit,
+        }
+    }
+
+    fn from_char(input: I, c: char) -> Self {
+        Self::Parse(VerboseError::from_char(input, c))
+    }
+}
+
+impl<I> ContextError<I> for Error<I> {
+    fn add_context(input: I, ctx: &'static str, other: Self) -> Self {
+        match other {
+            Self::Parse(error) => Self::Parse(VerboseError::add_context(input, ctx, error)),
+            limit => limit,
+        }
+    }
+}
+
+impl<I, E> FromExternalError<I, E> for Error<I> {
+    fn from_external_error(input: I, kind: ErrorKind, e: E) -> Self {
+        Self::Parse(VerboseError::from_external_error(input, kind, e))
+    }
+}
+
+impl<I: fmt::Display> fmt::Display for Error<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(error) => write!(f, "{error}"),
+            Self::Limit { limit, .. } => write!(f, "{limit} limit exceeded"),
+        }
+    }
+}
+
+impl<I: fmt::Debug + fmt::Display> std::error::Error for Error<I> {}
@@ -0,0 +1,507 @@
+use crate::{
+    escaping::parameter,
+    parser::{visit_openmetrics, visit_openmetrics2, visit_prometheus, Error},
+    Escaping, Exposition, Family, Label, Limits, MetricDescriptor, Sample, Visitor,
+};
+use nom::IResult;
+
+/// The format of an exposition
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    /// Parse with [`prometheus()`](crate::parser::prometheus)
+    Prometheus,
+    /// Parse with [`openmetrics()`](crate::parser::openmetrics)
+    OpenMetrics,
+    /// Parse with [`openmetrics2()`](crate::parser::openmetrics2)
+    OpenMetrics2,
+}
+
+impl Format {
+    /// The format of an HTTP `Content-Type` header value
+    ///
+    /// OpenMetrics versions are selected by the `version` parameter, such as
+    /// `application/openmetrics-text; version=2.0.0`.  Returns `None` for other media types.
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let media_type = content_type.split(';').next()?.trim();
+        let version = parameter(content_type, "version");
+
+        if media_type.eq_ignore_ascii_case("text/plain") {
+            Some(Self::Prometheus)
+        } else if media_type.eq_ignore_ascii_case("application/openmetrics-text") {
+            match version {
+                Some(version) if version.starts_with("2.") => Some(Self::OpenMetrics2),
+                _ => Some(Self::OpenMetrics),
+            }
+        } else {
+            None
+        }
+    }
+}
+
+/// A parser that reuses allocations between expositions
+///
+/// Expositions scraped from the same target have nearly the same shape each time.  An
+/// `ExpositionParser` keeps the `Vec`s of families, descriptors, samples, and labels from
+/// previous expositions and fills them again instead of allocating new ones.
+///
+/// ```
+/// use nom_openmetrics::{parser::{ExpositionParser, Format}, Exposition};
+///
+/// let mut parser = ExpositionParser::new(Format::Prometheus);
+/// let mut input = String::from("up{job=\"prometheus\"} 1\n");
+///
+/// let (_, exposition) = parser.parse(&input).unwrap();
+///
+/// assert_eq!(1.0, exposition.families()[0].samples[0].number());
+///
+/// parser.recycle(exposition);
+///
+/// input.clear();
+/// input.push_str("up{job=\"prometheus\"} 0\n");
+///
+/// let (_, exposition) = parser.parse(&input).unwrap();
+///
+/// assert_eq!(0.0, exposition.families()[0].samples[0].number());
+/// ```
+///
+/// Return each `Exposition` with [`recycle()`](Self::recycle) once you are done with it so the
+/// next [`parse()`](Self::parse) can reuse its allocations.
+///
+/// When every input outlives the exposition, [`parse_into()`](Self::parse_into) refills one
+/// `Exposition` in place:
+///
+/// ```
+/// use nom_openmetrics::{parser::{ExpositionParser, Format}, Exposition};
+///
+/// let first = String::from("up{job=\"prometheus\"} 1\n");
+/// let second = String::from("up{job=\"prometheus\"} 0\n");
+///
+/// let mut parser = ExpositionParser::new(Format::Prometheus);
+/// let mut exposition = Exposition::default();
+///
+/// parser.parse_into(&mut exposition, &first).unwrap();
+/// parser.parse_into(&mut exposition, &second).unwrap();
+///
+/// assert_eq!(0.0, exposition.families()[0].samples[0].number());
+/// ```
+#[derive(Debug)]
+pub struct ExpositionParser {
+    format: Format,
+    limits: Limits,
+    unescaping: Option<Escaping>,
+    buffers: Buffers,
+}
+
+impl ExpositionParser {
+    /// Create an `ExpositionParser` for `format`
+    pub fn new(format: Format) -> Self {
+        Self {
+            format,
+            limits: Limits::default(),
+            unescaping: None,
+            buffers: Buffers::default(),
+        }
+    }
+
+    /// Parse within `limits`
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+
+        self
+    }
+
+    /// Unescape metric and label names escaped with `escaping` after parsing
+    ///
+    /// Use [`Escaping::Values`] to recover UTF-8 names from targets that `U__`-encode them for
+    /// older consumers.  See [`Exposition::unescape_names()`]
+    pub fn with_unescaping(mut self, escaping: Escaping) -> Self {
+        self.unescaping = Some(escaping);
+
+        self
+    }
+
+    /// Parse `input` into a new [`Exposition`] using recycled allocations
+    ///
+    /// On error the allocations are kept for the next parse.
+    pub fn parse<'a>(
+        &mut self,
+        input: &'a str,
+    ) -> IResult<&'a str, Exposition<'a>, Error<&'a str>> {
+        let mut families = erase(std::mem::take(&mut self.buffers.families));
+
+        let rest = match self.visit(&mut families, input) {
+            Ok(rest) => rest,
+            Err(error) => {
+                self.buffers.families = erase(families);
+
+                return Err(error);
+            }
+        };
+
+        let mut exposition = Exposition::new(families);
+
+        if let Some(escaping) = self.unescaping {
+            exposition.unescape_names(escaping);
+        }
+
+        Ok((rest, exposition))
+    }
+
+    /// Parse `input` into `exposition`, replacing its contents and reusing its allocations
+    ///
+    /// The families of `exposition` borrow from every input parsed into it, so each input must
+    /// outlive `exposition`.  To reuse one input buffer between scrapes use
+    /// [`parse()`](Self::parse) and [`recycle()`](Self::recycle) instead.  On error `exposition`
+    /// is left empty.
+    pub fn parse_into<'a>(
+        &mut self,
+        exposition: &mut Exposition<'a>,
+        input: &'a str,
+    ) -> IResult<&'a str, (), Error<&'a str>> {
+        let families = exposition.families_mut();
+
+        self.buffers.recycle(families);
+
+        let rest = self.visit(families, input)?;
+
+        if let Some(escaping) = self.unescaping {
+            exposition.unescape_names(escaping);
+        }
+
+        Ok((rest, ()))
+    }
+
+    /// Keep the allocations of `exposition` for parsing the next exposition
+    pub fn recycle(&mut self, exposition: Exposition<'_>) {
+        let mut families = exposition.into_families();
+
+        self.buffers.recycle(&mut families);
+
+        if families.capacity() > self.buffers.families.capacity() {
+            self.buffers.families = erase(families);
+        }
+    }
+
+    /// Parse `input` appending to `families`, which is emptied into the buffers on error
+    fn visit<'a>(
+        &mut self,
+        families: &mut Vec<Family<'a>>,
+        input: &'a str,
+    ) -> Result<&'a str, nom::Err<Error<&'a str>>> {
+        let mut collector = Collector {
+            families,
+            buffers: &mut self.buffers,
+            descriptors: vec![],
+            samples: vec![],
+        };
+
+        let parsed = match self.format {
+            Format::Prometheus => visit_prometheus(input, &self.limits, &mut collector),
+            Format::OpenMetrics => visit_openmetrics(input, &self.limits, &mut collector),
+            Format::OpenMetrics2 => visit_openmetrics2(input, &self.limits, &mut collector),
+        };
+
+        match parsed {
+            Ok((rest, ())) => Ok(rest),
+            Err(error) => {
+                collector.on_family_end();
+
+                self.buffers.recycle(families);
+
+                Err(error)
+            }
+        }
+    }
+}
+
+/// Empty `Vec`s kept for reuse
+///
+/// The lifetimes of the contents are erased so buffers can outlive the input they were filled
+/// from.
+#[derive(Debug, Default)]
+struct Buffers {
+    families: Vec<Family<'static>>,
+    descriptors: Vec<Vec<MetricDescriptor<'static>>>,
+    samples: Vec<Vec<Sample<'static>>>,
+    labels: Vec<Vec<Label<'static>>>,
+}
+
+impl Buffers {
+    fn descriptors<'a>(&mut self) -> Vec<MetricDescriptor<'a>> {
+        self.descriptors.pop().map(erase).unwrap_or_default()
+    }
+
+    fn labels<'a>(&mut self) -> Vec<Label<'a>> {
+        self.labels.pop().map(erase).unwrap_or_default()
+    }
+
+    fn samples<'a>(&mut self) -> Vec<Sample<'a>> {
+        self.samples.pop().map(erase).unwrap_or_default()
+    }
+
+    /// Empty `families` keeping the allocations of their contents
+    ///
+    /// Buffers are kept in reverse so they are reused in exposition order.
+    fn recycle(&mut self, families: &mut Vec<Family<'_>>) {
+        for family in families.drain(..).rev() {
+            let Family {
+                descriptors,
+                mut samples,
+            } = family;
+
+            for sample in samples.drain(..).rev() {
+                let labels = sample.into_labels();
+
+                if labels.capacity() > 0 {
+                    self.labels.push(erase(labels));
+                }
+            }
+
+            if descriptors.capacity() > 0 {
+                self.descriptors.push(erase(descriptors));
+            }
+
+            if samples.capacity() > 0 {
+                self.samples.push(erase(samples));
+            }
+        }
+    }
+}
+
+/// A type with one lifetime that buffers can be erased from
+///
+/// # Safety
+///
+/// `With<'b>` must be `Self` with its lifetime replaced by `'b`, so both have the same layout.
+unsafe trait Erase {
+    type With<'b>;
+}
+
+// SAFETY: each `With<'b>` is the implementing type with its lifetime replaced by `'b`.
+unsafe impl Erase for Family<'_> {
+    type With<'b> = Family<'b>;
+}
+
+// SAFETY: see above
+unsafe impl Erase for MetricDescriptor<'_> {
+    type With<'b> = MetricDescriptor<'b>;
+}
+
+// SAFETY: see above
+unsafe impl Erase for Sample<'_> {
+    type With<'b> = Sample<'b>;
+}
+
+// SAFETY: see above
+unsafe impl Erase for Label<'_> {
+    type With<'b> = Label<'b>;
+}
+
+/// Empty `buffer` and change the lifetime of its element type, keeping its allocation
+fn erase<'b, T: Erase>(mut buffer: Vec<T>) -> Vec<T::With<'b>> {
+    buffer.clear();
+
+    let mut buffer = std::mem::ManuallyDrop::new(buffer);
+    let capacity = buffer.capacity();
+
+    // SAFETY: `T::With<'b>` is `T` with a different lifetime, so the allocation a `Vec<T>` made
+    // for `capacity` elements has the layout a `Vec<T::With<'b>>` of that capacity expects.  The
+    // length is zero so no value outlives the lifetime it was created with, and `ManuallyDrop`
+    // keeps the allocation from being freed twice.
+    unsafe { Vec::from_raw_parts(buffer.as_mut_ptr().cast(), 0, capacity) }
+}
+
+/// A [`Visitor`] that collects [`Family`]s into recycled buffers
+struct Collector<'b, 'a> {
+    families: &'b mut Vec<Family<'a>>,
+    buffers: &'b mut Buffers,
+    descriptors: Vec<MetricDescriptor<'a>>,
+    samples: Vec<Sample<'a>>,
+}
+
+impl<'a> Visitor<'a> for Collector<'_, 'a> {
+    fn on_descriptor(&mut self, descriptor: MetricDescriptor<'a>) {
+        if self.descriptors.capacity() == 0 {
+            self.descriptors = self.buffers.descriptors();
+        }
+
+        self.descriptors.push(descriptor);
+    }
+
+    fn on_sample(&mut self, sample: Sample<'a>) {
+        if self.samples.capacity() == 0 {
+            self.samples = self.buffers.samples();
+        }
+
+        self.samples.push(sample);
+    }
+
+    fn on_family_end(&mut self) {
+        self.families.push(Family::new(
+            std::mem::take(&mut self.descriptors),
+            std::mem::take(&mut self.samples),
+        ));
+    }
+
+    fn labels_buffer(&mut self) -> Vec<Label<'a>> {
+        self.buffers.labels()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    const INPUT: &str = "# TYPE up gauge\nup{job=\"prometheus\"} 1\nup{job=\"grafana\"} 0\n# TYPE requests counter\nrequests 1027\n";
+
+    #[test]
+    fn parse() {
+        let mut parser = ExpositionParser::new(Format::Prometheus);
+
+        let (_, exposition) = parser.parse(INPUT).unwrap();
+
+        let families = exposition.families().as_ptr().cast::<()>();
+        let samples = exposition.families()[0].samples.as_ptr().cast::<()>();
+        let labels = exposition.families()[0].samples[1]
+            .labels()
+            .as_ptr()
+            .cast::<()>();
+
+        parser.recycle(exposition);
+
+        let input = INPUT.replace(" 1\n", " 0\n");
+
+        let (_, exposition) = parser.parse(&input).unwrap();
+
+        assert_eq!(families, exposition.families().as_ptr().cast::<()>());
+        assert_eq!(
+            samples,
+            exposition.families()[0].samples.as_ptr().cast::<()>()
+        );
+        assert_eq!(
+            labels,
+            exposition.families()[0].samples[1]
+                .labels()
+                .as_ptr()
+                .cast::<()>()
+        );
+
+        let expected = crate::parser::prometheus(&input).unwrap().1;
+
+        assert_eq!(expected, exposition.into_families());
+    }
+
+    #[rstest]
+    #[case("text/plain; version=0.0.4; charset=utf-8", Some(Format::Prometheus))]
+    #[case(
+        "application/openmetrics-text; version=1.0.0; charset=utf-8",
+        Some(Format::OpenMetrics)
+    )]
+    #[case("application/openmetrics-text", Some(Format::OpenMetrics))]
+    #[case(
+        "application/openmetrics-text; version=2.0.0",
+        Some(Format::OpenMetrics2)
+    )]
+    #[case(
+        "Application/OpenMetrics-Text;Version=\"2.0.0\"",
+        Some(Format::OpenMetrics2)
+    )]
+    #[case("application/json", None)]
+    fn from_content_type(#[case] content_type: &str, #[case] expected: Option<Format>) {
+        assert_eq!(expected, Format::from_content_type(content_type));
+    }
+
+    #[test]
+    fn recycle() {
+        let mut parser = ExpositionParser::new(Format::OpenMetrics);
+        let mut input = format!("{INPUT}# EOF\n");
+
+        let (_, exposition) = parser.parse(&input).unwrap();
+
+        let samples = exposition.families()[1].samples.as_ptr().cast::<()>();
+
+        parser.recycle(exposition);
+
+        input.clear();
+        input.push_str("# TYPE up gauge\nup 1\n# TYPE requests counter\nrequests 1\n# EOF\n");
+
+        let (_, exposition) = parser.parse(&input).unwrap();
+
+        assert_eq!(
+            samples,
+            exposition.families()[1].samples.as_ptr().cast::<()>()
+        );
+        assert_eq!(2, exposition.families().len());
+    }
+
+    #[test]
+    fn parse_error() {
+        let mut parser = ExpositionParser::new(Format::OpenMetrics);
+        let input = format!("{INPUT}# EOF\n");
+
+        let (_, exposition) = parser.parse(&input).unwrap();
+        let families = exposition.families().as_ptr().cast::<()>();
+
+        parser.recycle(exposition);
+
+        assert!(parser.parse(INPUT).is_err());
+        assert_eq!(families, parser.buffers.families.as_ptr().cast::<()>());
+        assert_eq!(2, parser.buffers.samples.len());
+
+        let (_, exposition) = parser.parse(&input).unwrap();
+
+        assert_eq!(families, exposition.families().as_ptr().cast::<()>());
+    }
+
+    #[test]
+    fn parse_into() {
+        let mut parser = ExpositionParser::new(Format::Prometheus);
+        let mut exposition = Exposition::default();
+
+        parser.parse_into(&mut exposition, INPUT).unwrap();
+
+        let families = exposition.families().as_ptr();
+        let samples = exposition.families()[0].samples.as_ptr();
+
+        let input = INPUT.replace(" 1\n", " 0\n");
+
+        parser.parse_into(&mut exposition, &input).unwrap();
+
+        assert_eq!(families, exposition.families().as_ptr());
+        assert_eq!(samples, exposition.families()[0].samples.as_ptr());
+
+        let expected = crate::parser::prometheus(&input).unwrap().1;
+
+        assert_eq!(expected, exposition.into_families());
+    }
+
+    #[test]
+    fn parse_into_error() {
+        let mut parser = ExpositionParser::new(Format::OpenMetrics);
+        let mut exposition = Exposition::default();
+        let input = format!("{INPUT}# EOF\n");
+
+        parser.parse_into(&mut exposition, &input).unwrap();
+
+        let families = exposition.families().as_ptr();
+
+        assert!(parser.parse_into(&mut exposition, INPUT).is_err());
+        assert!(exposition.is_empty());
+        assert_eq!(families, exposition.families().as_ptr());
+    }
+
+    #[test]
+    fn with_unescaping() {
+        let mut parser =
+            ExpositionParser::new(Format::Prometheus).with_unescaping(Escaping::Values);
+
+        let (_, exposition) = parser
+            .parse("# TYPE U__my_2e_up gauge\nU__my_2e_up{U__my_2e_job=\"a\"} 1\n")
+            .unwrap();
+
+        let family = exposition.family("my.up").expect("unescaped family");
+
+        assert_eq!(Some("a"), family.samples[0].labels().get("my.job"));
+    }
+}
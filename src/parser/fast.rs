@@ -0,0 +1,172 @@
+use crate::{
+    parser::limits::exceeds,
+    parser::{
+        label::{is_metric_label_end, is_metric_label_start},
+        metric_name::{is_metric_name_char, is_metric_name_initial_char},
+    },
+    Label, Limits, Sample,
+};
+use memchr::{memchr, memchr2};
+
+/// Parse a sample of the common form `name{label="value",...} number\n`
+///
+/// Returns `None` for any other input, such as label values with escapes, so the caller can fall
+/// back to the complete grammar.  Any sample this accepts must parse identically with the
+/// complete grammar.  Samples exceeding `limits` are left to the complete grammar to report.
+pub(crate) fn sample<'a>(
+    input: &'a str,
+    labels: &mut Vec<Label<'a>>,
+    limits: &Limits,
+) -> Option<(&'a str, Sample<'a>)> {
+    let end = memchr(b'\n', input.as_bytes())?;
+    let line = &input[..end];
+    let bytes = line.as_bytes();
+
+    let mut i = identifier(bytes, 0, is_metric_name_initial_char, is_metric_name_char)?;
+    let name = &line[..i];
+
+    labels.clear();
+
+    if bytes.get(i) == Some(&b'{') {
+        i += 1;
+
+        if bytes.get(i) != Some(&b'}') {
+            loop {
+                let start = i;
+                i = identifier(bytes, i, is_metric_label_start, is_metric_label_end)?;
+                let label_name = &line[start..i];
+
+                if bytes.get(i..i + 2) != Some(b"=\"") {
+                    return None;
+                }
+
+                i += 2;
+
+                let value_start = i;
+                i += memchr2(b'"', b'\\', &bytes[i..])?;
+
+                if bytes[i] != b'"'
+                    || exceeds(limits.max_labels, labels.len() + 1)
+                    || exceeds(limits.max_label_name_length, label_name.len())
+                    || exceeds(limits.max_label_value_length, i - value_start)
+                {
+                    return None;
+                }
+
+                labels.push(Label::new(label_name, line[value_start..i].into()));
+                i += 1;
+
+                match bytes.get(i) {
+                    Some(b',') => i += 1,
+                    Some(b'}') => break,
+                    _ => return None,
+                }
+            }
+        }
+
+        i += 1;
+    }
+
+    if bytes.get(i) != Some(&b' ') {
+        return None;
+    }
+
+    let number = number(&line[i + 1..])?;
+
+    let sample = if labels.is_empty() {
+        Sample::new(name, number)
+    } else {
+        Sample::with_labels(name, number, std::mem::take(labels))
+    };
+
+    Some((&input[end + 1..], sample))
+}
+
+/// Recognize the metric name at the start of `input`
+pub(crate) fn metric_name(input: &str) -> Option<&str> {
+    let end = identifier(
+        input.as_bytes(),
+        0,
+        is_metric_name_initial_char,
+        is_metric_name_char,
+    )?;
+
+    Some(&input[..end])
+}
+
+/// Find the end of an identifier starting at `start`
+fn identifier(
+    bytes: &[u8],
+    start: usize,
+    initial: fn(char) -> bool,
+    rest: fn(char) -> bool,
+) -> Option<usize> {
+    if !initial(char::from(*bytes.get(start)?)) {
+        return None;
+    }
+
+    let len = bytes[start + 1..]
+        .iter()
+        .position(|&b| !rest(char::from(b)))
+        .unwrap_or(bytes.len() - start - 1);
+
+    Some(start + 1 + len)
+}
+
+/// Parse a finite decimal number, leaving infinities and NaN to the complete grammar
+fn number(value: &str) -> Option<f64> {
+    let decimal = |b: u8| b.is_ascii_digit() || matches!(b, b'.' | b'e' | b'E' | b'+' | b'-');
+
+    if value.is_empty() || !value.bytes().all(decimal) {
+        return None;
+    }
+
+    value.parse().ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("up 1\n")]
+    #[case("up{} 1\n")]
+    #[case("up{job=\"prometheus\"} 2\n")]
+    #[case("up{job=\"prometheus\",instance=\"localhost:9090\"} -2.5e-3\n")]
+    #[case("up{job=\"☃\"} 1\n")]
+    #[case("up{job=\"\"} .5\n")]
+    #[case("rule:up 1.\n")]
+    fn sample_fast(#[case] input: &str) {
+        let (rest, fast) = sample(input, &mut vec![], &Limits::default()).expect("fast path");
+
+        let (expected_rest, expected) = crate::parser::sample_grammar(
+            input,
+            &mut vec![],
+            crate::parser::Version::V1,
+            &Limits::default(),
+        )
+        .unwrap();
+
+        assert_eq!(expected, fast);
+        assert_eq!(expected_rest, rest);
+    }
+
+    #[rstest]
+    #[case("up +Inf\n")]
+    #[case("up NaN\n")]
+    #[case("up{job=\"a\\\"b\"} 1\n")]
+    #[case("up{job=\"a\\\\b\"} 1\n")]
+    #[case("up 1")]
+    #[case("up 1 1700000000\n")]
+    #[case("up 1e\n")]
+    #[case("up  1\n")]
+    #[case("up{job=\"a\",} 1\n")]
+    #[case("up{job=\"a\"\n")]
+    #[case("up{0=\"a\"} 1\n")]
+    #[case("0up 1\n")]
+    #[case("up\n")]
+    fn sample_fallback(#[case] input: &str) {
+        assert_eq!(None, sample(input, &mut vec![], &Limits::default()));
+    }
+}
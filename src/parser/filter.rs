@@ -0,0 +1,270 @@
+use crate::{
+    metric_descriptor::{strip_suffix, SUFFIXES},
+    parser::{verbose, visit_openmetrics, visit_prometheus, Error},
+    Family, Limits, MetricDescriptor, Sample, Visitor,
+};
+use nom::IResult;
+use nom_language::error::VerboseError;
+use std::collections::HashSet;
+
+/// Parse an OpenMetrics-format exposition keeping only families whose name matches `predicate`
+///
+/// See [`prometheus_filtered()`]
+pub fn openmetrics_filtered<F>(
+    input: &str,
+    predicate: F,
+) -> IResult<&str, Vec<Family<'_>>, VerboseError<&str>>
+where
+    F: FnMut(&str) -> bool,
+{
+    openmetrics_filtered_with_limits(input, &Limits::default(), predicate).map_err(verbose)
+}
+
+/// Parse an OpenMetrics-format exposition within `limits` keeping only families whose name
+/// matches `predicate`
+///
+/// Skipped sample lines are still subject to the line length, samples, and families limits.  See
+/// [`openmetrics_filtered()`]
+pub fn openmetrics_filtered_with_limits<'a, F>(
+    input: &'a str,
+    limits: &Limits,
+    predicate: F,
+) -> IResult<&'a str, Vec<Family<'a>>, Error<&'a str>>
+where
+    F: FnMut(&str) -> bool,
+{
+    let mut filtered = Filtered::new(predicate);
+
+    let (rest, ()) = visit_openmetrics(input, limits, &mut filtered)?;
+
+    Ok((rest, filtered.families))
+}
+
+/// Parse a Prometheus-format exposition keeping only families whose name matches `predicate`
+///
+/// The family name is the metric name of its descriptors, or the name of each sample without a
+/// suffix such as `_total` or `_count` for a family without descriptors.  Sample lines of
+/// families that do not match are skipped without parsing their labels or value.  Samples with a
+/// quoted name, as in OpenMetrics 2.0, are never skipped.
+///
+/// ```
+/// use nom_openmetrics::parser::{prometheus_filtered, NameFilter};
+///
+/// let input = "# TYPE up gauge\nup 1\n# TYPE requests counter\nrequests 1027\n";
+/// let filter = NameFilter::allow(["up"]);
+///
+/// let (_, families) = prometheus_filtered(input, |name| filter.matches(name)).unwrap();
+///
+/// assert_eq!(1, families.len());
+/// ```
+pub fn prometheus_filtered<F>(
+    input: &str,
+    predicate: F,
+) -> IResult<&str, Vec<Family<'_>>, VerboseError<&str>>
+where
+    F: FnMut(&str) -> bool,
+{
+    prometheus_filtered_with_limits(input, &Limits::default(), predicate).map_err(verbose)
+}
+
+/// Parse a Prometheus-format exposition within `limits` keeping only families whose name
+/// matches `predicate`
+///
+/// Skipped sample lines are still subject to the line length, samples, and families limits.  See
+/// [`prometheus_filtered()`]
+pub fn prometheus_filtered_with_limits<'a, F>(
+    input: &'a str,
+    limits: &Limits,
+    predicate: F,
+) -> IResult<&'a str, Vec<Family<'a>>, Error<&'a str>>
+where
+    F: FnMut(&str) -> bool,
+{
+    let mut filtered = Filtered::new(predicate);
+
+    let (rest, ()) = visit_prometheus(input, limits, &mut filtered)?;
+
+    Ok((rest, filtered.families))
+}
+
+/// An allow or deny list of metric names
+#[derive(Clone, Debug, PartialEq)]
+pub enum NameFilter {
+    /// Match only these names
+    Allow(HashSet<String>),
+    /// Match all names except these
+    Deny(HashSet<String>),
+}
+
+impl NameFilter {
+    /// Create a `NameFilter` matching only `names`
+    pub fn allow<I, S>(names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self::Allow(names.into_iter().map(Into::into).collect())
+    }
+
+    /// Create a `NameFilter` matching all names except `names`
+    pub fn deny<I, S>(names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self::Deny(names.into_iter().map(Into::into).collect())
+    }
+
+    /// Returns `true` if `name` passes the filter
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            NameFilter::Allow(names) => names.contains(name),
+            NameFilter::Deny(names) => !names.contains(name),
+        }
+    }
+}
+
+/// A [`Visitor`] that collects [`Family`]s whose name matches a predicate
+struct Filtered<'a, F> {
+    predicate: F,
+    families: Vec<Family<'a>>,
+    descriptors: Vec<MetricDescriptor<'a>>,
+    samples: Vec<Sample<'a>>,
+}
+
+impl<F> Filtered<'_, F> {
+    fn new(predicate: F) -> Self {
+        Self {
+            predicate,
+            families: vec![],
+            descriptors: vec![],
+            samples: vec![],
+        }
+    }
+}
+
+impl<'a, F> Visitor<'a> for Filtered<'a, F>
+where
+    F: FnMut(&str) -> bool,
+{
+    fn on_descriptor(&mut self, descriptor: MetricDescriptor<'a>) {
+        self.descriptors.push(descriptor);
+    }
+
+    fn wants_sample(&mut self, name: &'a str) -> bool {
+        let name = match self.descriptors.first() {
+            Some(descriptor) => descriptor.metric(),
+            None => strip_suffix(name, &SUFFIXES),
+        };
+
+        (self.predicate)(name)
+    }
+
+    fn on_sample(&mut self, sample: Sample<'a>) {
+        self.samples.push(sample);
+    }
+
+    fn on_family_end(&mut self) {
+        let descriptors = std::mem::take(&mut self.descriptors);
+
+        if !self.samples.is_empty() {
+            self.families
+                .push(Family::new(descriptors, std::mem::take(&mut self.samples)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parser::prometheus, test::parse, MetricType};
+
+    const INPUT: &str = "bare 1\nother 2\n# TYPE up gauge\nup{job=\"prometheus\"} 1\n# TYPE requests counter\nrequests 1027\n# TYPE latency summary\nlatency{quantile=\"0.5\"} 0.25\nlatency_count 3\nlatency_sum 1.5\n";
+
+    #[test]
+    fn prometheus_filtered() {
+        let filter = NameFilter::allow(["latency", "other"]);
+
+        let (rest, families) = parse(
+            |input| super::prometheus_filtered(input, |name| filter.matches(name)),
+            INPUT,
+        );
+
+        assert!(rest.is_empty(), "leftover: {rest:?}");
+        assert_eq!(
+            vec![
+                Family::new(vec![], vec![Sample::new("other", 2.0)]),
+                Family::new(
+                    vec![MetricDescriptor::r#type("latency", MetricType::Summary)],
+                    vec![
+                        Sample::new("latency", 0.25).add_label("quantile", "0.5"),
+                        Sample::new("latency_count", 3.0),
+                        Sample::new("latency_sum", 1.5),
+                    ]
+                )
+            ],
+            families
+        );
+    }
+
+    #[test]
+    fn prometheus_filtered_all() {
+        let (_, expected) = parse(prometheus, INPUT);
+
+        let (_, families) = parse(|input| super::prometheus_filtered(input, |_| true), INPUT);
+
+        assert_eq!(expected, families);
+    }
+
+    #[test]
+    fn prometheus_filtered_without_descriptors() {
+        let input = "up 1\nrequests 1027\n";
+        let filter = NameFilter::deny(["up"]);
+
+        let (_, families) = parse(
+            |input| super::prometheus_filtered(input, |name| filter.matches(name)),
+            input,
+        );
+
+        assert_eq!(
+            vec![Family::new(vec![], vec![Sample::new("requests", 1027.0)])],
+            families
+        );
+    }
+
+    #[test]
+    fn prometheus_filtered_without_descriptors_suffix() {
+        let input = "latency_count 3\nlatency_sum 1.5\nrequests_total 1027\n";
+        let filter = NameFilter::allow(["latency"]);
+
+        let (_, families) = parse(
+            |input| super::prometheus_filtered(input, |name| filter.matches(name)),
+            input,
+        );
+
+        assert_eq!(
+            vec![Family::new(
+                vec![],
+                vec![
+                    Sample::new("latency_count", 3.0),
+                    Sample::new("latency_sum", 1.5)
+                ]
+            )],
+            families
+        );
+    }
+
+    #[test]
+    fn openmetrics_filtered() {
+        let input = format!("{INPUT}# EOF\n");
+
+        let (rest, families) = parse(
+            |input| super::openmetrics_filtered(input, |name| name == "up"),
+            &input,
+        );
+
+        assert!(rest.is_empty(), "leftover: {rest:?}");
+        assert_eq!(1, families.len());
+        assert_eq!("up", families[0].samples[0].name());
+    }
+}
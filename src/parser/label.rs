@@ -1,50 +1,128 @@
-use crate::{parser::string, Label};
+use crate::{
+    parser::{limits::exceeds, string, Error},
+    Label, Limit, Limits,
+};
 use nom::{
     bytes::complete::{take_while, take_while1},
     character::complete::char,
-    combinator::{map, recognize},
+    combinator::recognize,
     error::context,
-    multi::separated_list0,
-    sequence::{delimited, preceded, separated_pair},
+    sequence::{delimited, preceded},
     IResult, Parser,
 };
 use nom_language::error::VerboseError;
+use std::borrow::Cow;
 
-fn is_metric_label_start(c: char) -> bool {
+pub(crate) fn is_metric_label_start(c: char) -> bool {
     c.is_ascii_alphabetic() || c == '_'
 }
 
-fn is_metric_label_end(c: char) -> bool {
+pub(crate) fn is_metric_label_end(c: char) -> bool {
     c.is_ascii_alphanumeric() || c == '_'
 }
 
-/// Parse a `Label` for a metric
-pub(crate) fn label(input: &str) -> IResult<&str, Label<'_>, VerboseError<&str>> {
-    context(
+/// Returns `true` if `name` is a label name that does not need quotes
+pub(crate) fn is_metric_label(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars.next().is_some_and(is_metric_label_start) && chars.all(is_metric_label_end)
+}
+
+/// A parser for one label within [`Limits`]
+pub(crate) type LabelParser =
+    for<'a> fn(&'a str, &Limits) -> IResult<&'a str, Label<'a>, Error<&'a str>>;
+
+/// Parse a `Label` for a metric within `limits`
+///
+/// Lengths are checked before the value is unescaped.
+pub(crate) fn label<'a>(
+    input: &'a str,
+    limits: &Limits,
+) -> IResult<&'a str, Label<'a>, Error<&'a str>> {
+    let (rest, name) = metric_label(input).map_err(|e| e.map(Error::Parse))?;
+
+    label_value(input, rest, name.into(), limits)
+}
+
+/// Parse the `=` and value of a label named `name` within `limits`
+///
+/// `input` is the start of the label, where a limit error is reported.
+pub(crate) fn label_value<'a>(
+    input: &'a str,
+    rest: &'a str,
+    name: Cow<'a, str>,
+    limits: &Limits,
+) -> IResult<&'a str, Label<'a>, Error<&'a str>> {
+    if exceeds(limits.max_label_name_length, name.len()) {
+        return Err(Error::limit(input, Limit::LabelNameLength));
+    }
+
+    let (rest, value) = context(
         "label",
-        map(
-            separated_pair(metric_label, char('='), label_value),
-            |(name, value)| Label { name, value },
-        ),
+        preceded(char('='), |input| {
+            string::label_within(
+                input,
+                limits.max_label_value_length,
+                Limit::LabelValueLength,
+            )
+        }),
     )
-    .parse(input)
+    .parse(rest)?;
+
+    Ok((rest, Label::new(name, value)))
 }
 
-/// Parse `Label`s for a metric
-pub(crate) fn labels(input: &str) -> IResult<&str, Vec<Label<'_>>, VerboseError<&str>> {
+/// Parse `Label`s for a metric within `limits`, appending them to `labels`
+pub(crate) fn labels_into<'a>(
+    input: &'a str,
+    labels: &mut Vec<Label<'a>>,
+    limits: &Limits,
+) -> IResult<&'a str, (), Error<&'a str>> {
     context(
         "labels",
-        delimited(char('{'), separated_list0(char(','), label), char('}')),
+        delimited(
+            char('{'),
+            |input| label_list(input, labels, limits, label),
+            char('}'),
+        ),
     )
     .parse(input)
 }
 
-fn label_value(input: &str) -> IResult<&str, String, VerboseError<&str>> {
-    string::label(input)
+/// Parse comma separated labels up to a closing `}`, appending them to `labels`
+///
+/// Parsing stops with a limit error before the label past `max_labels`.
+pub(crate) fn label_list<'a>(
+    mut input: &'a str,
+    labels: &mut Vec<Label<'a>>,
+    limits: &Limits,
+    label: LabelParser,
+) -> IResult<&'a str, (), Error<&'a str>> {
+    let start = labels.len();
+
+    loop {
+        if labels.len() > start {
+            match char::<_, Error<&str>>(',').parse(input) {
+                Ok((rest, _)) => input = rest,
+                Err(_) => return Ok((input, ())),
+            }
+        } else if input.starts_with('}') {
+            return Ok((input, ()));
+        }
+
+        if exceeds(limits.max_labels, labels.len() - start + 1) {
+            return Err(Error::limit(input, Limit::Labels));
+        }
+
+        let (rest, label) = label(input, limits)?;
+
+        labels.push(label);
+        input = rest;
+    }
 }
 
 /// Matches a metric name `[a-zA-Z_][a-zA-Z0-9_]*`
-fn metric_label(input: &str) -> IResult<&str, &str, VerboseError<&str>> {
+pub(crate) fn metric_label(input: &str) -> IResult<&str, &str, VerboseError<&str>> {
     context(
         "metric label",
         recognize(preceded(
@@ -58,7 +136,7 @@ fn metric_label(input: &str) -> IResult<&str, &str, VerboseError<&str>> {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::test::parse;
+    use crate::{parser::verbose, test::parse};
     use nom_language::error::VerboseErrorKind;
     use rstest::rstest;
 
@@ -66,9 +144,10 @@ mod test {
     #[case(r#"job="prometheus""#, Label::new("job", "prometheus".into()))]
     #[case(r#"job="☃""#, Label::new("job", "☃".into()))]
     fn label(#[case] input: &str, #[case] expected: Label<'_>) {
-        use crate::test::parse;
-
-        let (rest, label) = parse(super::label, input);
+        let (rest, label) = parse(
+            |input| super::label(input, &Limits::default()).map_err(verbose),
+            input,
+        );
 
         assert_eq!(expected, label, "input: {input}");
         assert!(rest.is_empty());
@@ -81,13 +160,42 @@ mod test {
             Label::new("job", "prometheus".into()),
             Label::new("instance", "scrape.example".into())
         ])]
-    fn labels(#[case] input: &str, #[case] expected: Vec<Label<'_>>) {
-        let (rest, labels) = parse(super::labels, input);
+    #[case("{}", vec![])]
+    fn labels_into(#[case] input: &str, #[case] expected: Vec<Label<'_>>) {
+        let mut labels = vec![];
+
+        let (rest, ()) = parse(
+            |input| super::labels_into(input, &mut labels, &Limits::default()).map_err(verbose),
+            input,
+        );
 
         assert_eq!(expected, labels, "input: {input}");
         assert!(rest.is_empty());
     }
 
+    #[rstest]
+    #[case(Limits { max_labels: Some(1), ..Limits::default() }, "b=\"2\"}", Limit::Labels)]
+    #[case(Limits { max_labels: Some(0), ..Limits::default() }, "a=\"1\",b=\"2\"}", Limit::Labels)]
+    #[case(Limits { max_label_name_length: Some(0), ..Limits::default() }, "a=\"1\",b=\"2\"}", Limit::LabelNameLength)]
+    #[case(Limits { max_label_value_length: Some(0), ..Limits::default() }, "\"1\",b=\"2\"}", Limit::LabelValueLength)]
+    fn labels_into_exceeded(
+        #[case] limits: Limits,
+        #[case] expected_input: &str,
+        #[case] expected_limit: Limit,
+    ) {
+        let mut labels = vec![];
+
+        let Err(nom::Err::Failure(Error::Limit { input, limit })) =
+            super::labels_into("{a=\"1\",b=\"2\"}", &mut labels, &limits)
+        else {
+            unreachable!("expected limit error");
+        };
+
+        assert_eq!(expected_limit, limit);
+        assert_eq!(expected_input, input);
+        assert!(labels.len() <= 1);
+    }
+
     #[test]
     fn metric_label_error() {
         let input = "0";
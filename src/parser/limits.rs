@@ -0,0 +1,123 @@
+use crate::{
+    parser::{metric_descriptor::descriptor_within, sample_into, Error, Version},
+    Label, Limit, Limits, MetricDescriptor, Sample,
+};
+use nom::IResult;
+
+/// Parse a metric descriptor within `limits`
+pub(crate) fn descriptor<'a>(
+    input: &'a str,
+    limits: &Limits,
+    version: Version,
+) -> IResult<&'a str, MetricDescriptor<'a>, Error<&'a str>> {
+    line(input, limits)?;
+
+    descriptor_within(input, limits, version)
+}
+
+/// Parse a single metric sample within `limits`, collecting its labels in the `labels` buffer
+pub(crate) fn sample<'a>(
+    input: &'a str,
+    limits: &Limits,
+    labels: &mut Vec<Label<'a>>,
+    version: Version,
+) -> IResult<&'a str, Sample<'a>, Error<&'a str>> {
+    line(input, limits)?;
+
+    sample_into(input, labels, version, limits)
+}
+
+/// Fail if the line at the start of `input` is longer than the line length limit
+///
+/// At most `max_line_length` bytes are examined.
+pub(crate) fn line<'a>(input: &'a str, limits: &Limits) -> Result<(), nom::Err<Error<&'a str>>> {
+    let Some(max) = limits.max_line_length else {
+        return Ok(());
+    };
+
+    let bytes = input.as_bytes();
+    let window = &bytes[..bytes.len().min(max.saturating_add(1))];
+
+    if window.len() > max && memchr::memchr(b'\n', window).is_none() {
+        return Err(Error::limit(input, Limit::LineLength));
+    }
+
+    Ok(())
+}
+
+/// Returns `true` if `count` is over the limit `max`
+pub(crate) fn exceeds(max: Option<usize>, count: usize) -> bool {
+    max.is_some_and(|max| count > max)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    fn limit<T: std::fmt::Debug>(result: IResult<&str, T, Error<&str>>) -> Limit {
+        match result {
+            Err(nom::Err::Failure(Error::Limit { limit, .. })) => limit,
+            result => unreachable!("expected limit error, got {result:?}"),
+        }
+    }
+
+    #[rstest]
+    #[case(Limits { max_line_length: Some(8), ..Limits::default() }, Limit::LineLength)]
+    #[case(Limits { max_labels: Some(1), ..Limits::default() }, Limit::Labels)]
+    #[case(Limits { max_label_name_length: Some(3), ..Limits::default() }, Limit::LabelNameLength)]
+    #[case(Limits { max_label_value_length: Some(4), ..Limits::default() }, Limit::LabelValueLength)]
+    fn sample_exceeded(#[case] limits: Limits, #[case] expected: Limit) {
+        let input = "up{job=\"prometheus\",instance=\"a\"} 1\n";
+
+        assert_eq!(
+            expected,
+            limit(sample(input, &limits, &mut vec![], Version::V1))
+        );
+    }
+
+    #[test]
+    fn sample_within() {
+        let input = "up{job=\"prometheus\"} 1\n";
+        let limits = Limits {
+            max_line_length: Some(23),
+            max_labels: Some(1),
+            max_label_name_length: Some(3),
+            max_label_value_length: Some(10),
+            ..Limits::default()
+        };
+
+        let (rest, _) = sample(input, &limits, &mut vec![], Version::V1).unwrap();
+
+        assert!(rest.is_empty(), "leftover: {rest:?}");
+    }
+
+    #[test]
+    fn descriptor_exceeded() {
+        let input = "# HELP up Is the job up\n";
+        let limits = Limits {
+            max_help_length: Some(12),
+            ..Limits::default()
+        };
+
+        assert_eq!(
+            Limit::HelpLength,
+            limit(descriptor(input, &limits, Version::V1))
+        );
+    }
+
+    #[rstest]
+    #[case("up 1\n", true)]
+    #[case("up 10\n", true)]
+    #[case("up 100\n", false)]
+    #[case("up 10", true)]
+    #[case("up 100", false)]
+    fn line_length(#[case] input: &str, #[case] within: bool) {
+        let limits = Limits {
+            max_line_length: Some(5),
+            ..Limits::default()
+        };
+
+        assert_eq!(within, line(input, &limits).is_ok(), "input: {input:?}");
+    }
+}
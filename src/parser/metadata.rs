@@ -0,0 +1,139 @@
+use crate::{
+    parser::{verbose, visit_openmetrics, visit_prometheus, Error},
+    Limits, Metadata, MetricDescriptor, Visitor,
+};
+use nom::IResult;
+use nom_language::error::VerboseError;
+
+/// Parse only the [`Metadata`] of an OpenMetrics-format exposition
+///
+/// See [`prometheus_metadata()`]
+pub fn openmetrics_metadata(input: &str) -> IResult<&str, Vec<Metadata<'_>>, VerboseError<&str>> {
+    openmetrics_metadata_with_limits(input, &Limits::default()).map_err(verbose)
+}
+
+/// Parse only the [`Metadata`] of an OpenMetrics-format exposition within `limits`
+///
+/// See [`openmetrics_metadata()`]
+pub fn openmetrics_metadata_with_limits<'a>(
+    input: &'a str,
+    limits: &Limits,
+) -> IResult<&'a str, Vec<Metadata<'a>>, Error<&'a str>> {
+    let mut metadata = Metadatas::default();
+
+    let (rest, ()) = visit_openmetrics(input, limits, &mut metadata)?;
+
+    Ok((rest, metadata.metadata))
+}
+
+/// Parse only the [`Metadata`] of a Prometheus-format exposition
+///
+/// Sample lines are counted without parsing or validating their labels or value.
+pub fn prometheus_metadata(input: &str) -> IResult<&str, Vec<Metadata<'_>>, VerboseError<&str>> {
+    prometheus_metadata_with_limits(input, &Limits::default()).map_err(verbose)
+}
+
+/// Parse only the [`Metadata`] of a Prometheus-format exposition within `limits`
+///
+/// Skipped sample lines are still subject to the line length, samples, and families limits.
+/// See [`prometheus_metadata()`]
+pub fn prometheus_metadata_with_limits<'a>(
+    input: &'a str,
+    limits: &Limits,
+) -> IResult<&'a str, Vec<Metadata<'a>>, Error<&'a str>> {
+    let mut metadata = Metadatas::default();
+
+    let (rest, ()) = visit_prometheus(input, limits, &mut metadata)?;
+
+    Ok((rest, metadata.metadata))
+}
+
+/// A [`Visitor`] that collects [`Metadata`] and skips all samples
+///
+/// Only the metric name of a sample line is parsed, so a sample with malformed labels or value
+/// is counted without causing an error.
+#[derive(Default)]
+struct Metadatas<'a> {
+    metadata: Vec<Metadata<'a>>,
+    descriptors: Vec<MetricDescriptor<'a>>,
+    series: usize,
+}
+
+impl<'a> Visitor<'a> for Metadatas<'a> {
+    fn on_descriptor(&mut self, descriptor: MetricDescriptor<'a>) {
+        self.descriptors.push(descriptor);
+    }
+
+    fn wants_sample(&mut self, _name: &'a str) -> bool {
+        self.series += 1;
+
+        false
+    }
+
+    fn on_family_end(&mut self) {
+        self.metadata.push(Metadata::new(
+            std::mem::take(&mut self.descriptors),
+            std::mem::take(&mut self.series),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{test::parse, MetricType};
+
+    #[test]
+    fn prometheus_metadata() {
+        let input = "# HELP up Is the job up\n# TYPE up gauge\nup{job=\"prometheus\"} 1\nup{job=\"grafana\"} 0\n# TYPE requests counter\n# UNIT requests requests\nrequests_total 1027\n";
+
+        let (rest, metadata) = parse(super::prometheus_metadata, input);
+
+        assert!(rest.is_empty(), "leftover: {rest:?}");
+        assert_eq!(
+            vec![
+                Metadata::new(
+                    vec![
+                        MetricDescriptor::help("up", "Is the job up".into()),
+                        MetricDescriptor::r#type("up", MetricType::Gauge),
+                    ],
+                    2
+                ),
+                Metadata::new(
+                    vec![
+                        MetricDescriptor::r#type("requests", MetricType::Counter),
+                        MetricDescriptor::unit("requests", "requests"),
+                    ],
+                    1
+                ),
+            ],
+            metadata
+        );
+    }
+
+    #[test]
+    fn prometheus_metadata_malformed_sample() {
+        let input = "# TYPE up gauge\nup{job=prometheus} one\n";
+
+        let (rest, metadata) = parse(super::prometheus_metadata, input);
+
+        assert!(rest.is_empty(), "leftover: {rest:?}");
+        assert_eq!(1, metadata[0].series);
+    }
+
+    #[test]
+    fn openmetrics_metadata() {
+        let input = "# TYPE up gauge\nup 1\n# EOF\n";
+
+        let (rest, metadata) = parse(super::openmetrics_metadata, input);
+
+        assert!(rest.is_empty(), "leftover: {rest:?}");
+        assert_eq!(
+            vec![Metadata::new(
+                vec![MetricDescriptor::r#type("up", MetricType::Gauge)],
+                1
+            )],
+            metadata
+        );
+    }
+}
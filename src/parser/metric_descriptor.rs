@@ -1,6 +1,6 @@
 use crate::{
-    parser::{metric_name, string},
-    MetricDescriptor, MetricType,
+    parser::{lift, metric_name, openmetrics2, string, verbose, Error, Version},
+    Limit, Limits, MetricDescriptor, MetricType,
 };
 use nom::{
     branch::alt,
@@ -12,18 +12,50 @@ use nom::{
     IResult, Parser,
 };
 use nom_language::error::VerboseError;
+use std::borrow::Cow;
 
 use super::metric_name::is_metric_name_char;
 
+/// A metric name parser
+type Name = fn(&str) -> IResult<&str, Cow<'_, str>, VerboseError<&str>>;
+
 pub(crate) fn metric_descriptor(
     input: &str,
 ) -> IResult<&str, MetricDescriptor<'_>, VerboseError<&str>> {
+    descriptor_within(input, &Limits::default(), Version::V1).map_err(verbose)
+}
+
+/// Parse an OpenMetrics 2.0 metric descriptor whose metric name may be quoted
+#[cfg(test)]
+fn quoted_metric_descriptor(
+    input: &str,
+) -> IResult<&str, MetricDescriptor<'_>, VerboseError<&str>> {
+    descriptor_within(input, &Limits::default(), Version::V2).map_err(verbose)
+}
+
+/// Parse a metric descriptor of `version` within `limits`
+///
+/// The HELP length is checked before the text is unescaped.
+pub(crate) fn descriptor_within<'a>(
+    input: &'a str,
+    limits: &Limits,
+    version: Version,
+) -> IResult<&'a str, MetricDescriptor<'a>, Error<&'a str>> {
+    let name: Name = match version {
+        Version::V1 => bare_metric_name,
+        Version::V2 => openmetrics2::metric_name,
+    };
+
     context(
         "metric decriptor",
         preceded(
             tag("# "),
             terminated(
-                alt((help_descriptor, type_descriptor, unit_descriptor)),
+                alt((
+                    move |input| help_descriptor(input, name, limits),
+                    move |input| lift(type_descriptor(input, name)),
+                    move |input| lift(unit_descriptor(input, name)),
+                )),
                 char('\n'),
             ),
         ),
@@ -31,6 +63,11 @@ pub(crate) fn metric_descriptor(
     .parse(input)
 }
 
+/// Parse an unquoted metric name
+fn bare_metric_name(input: &str) -> IResult<&str, Cow<'_, str>, VerboseError<&str>> {
+    map(metric_name, Cow::Borrowed).parse(input)
+}
+
 fn metric_type(input: &str) -> IResult<&str, MetricType<'_>, VerboseError<&str>> {
     context(
         "metric type",
@@ -38,6 +75,7 @@ fn metric_type(input: &str) -> IResult<&str, MetricType<'_>, VerboseError<&str>>
             map(tag("counter"), |_| MetricType::Counter),
             map(tag("gaugehistogram"), |_| MetricType::Gaugehistogram),
             map(tag("gauge"), |_| MetricType::Gauge),
+            map(tag("histogram"), |_| MetricType::Histogram),
             map(tag("info"), |_| MetricType::Info),
             map(tag("stateset"), |_| MetricType::Stateset),
             map(tag("summary"), |_| MetricType::Summary),
@@ -47,21 +85,30 @@ fn metric_type(input: &str) -> IResult<&str, MetricType<'_>, VerboseError<&str>>
     .parse(input)
 }
 
-fn help_descriptor(input: &str) -> IResult<&str, MetricDescriptor<'_>, VerboseError<&str>> {
+fn help_descriptor<'a>(
+    input: &'a str,
+    name: Name,
+    limits: &Limits,
+) -> IResult<&'a str, MetricDescriptor<'a>, Error<&'a str>> {
     map(
         (
-            preceded(tag("HELP "), metric_name),
-            preceded(char(' '), string::descriptor),
+            preceded(tag("HELP "), |input| lift(name(input))),
+            preceded(char(' '), |input| {
+                string::descriptor_within(input, limits.max_help_length, Limit::HelpLength)
+            }),
         ),
         |(metric, help)| MetricDescriptor::help(metric, help),
     )
     .parse(input)
 }
 
-fn type_descriptor(input: &str) -> IResult<&str, MetricDescriptor<'_>, VerboseError<&str>> {
+fn type_descriptor(
+    input: &str,
+    name: Name,
+) -> IResult<&str, MetricDescriptor<'_>, VerboseError<&str>> {
     map(
         (
-            preceded(tag("TYPE "), metric_name),
+            preceded(tag("TYPE "), name),
             preceded(char(' '), metric_type),
         ),
         |(metric, r#type)| MetricDescriptor::r#type(metric, r#type),
@@ -69,10 +116,13 @@ fn type_descriptor(input: &str) -> IResult<&str, MetricDescriptor<'_>, VerboseEr
     .parse(input)
 }
 
-fn unit_descriptor(input: &str) -> IResult<&str, MetricDescriptor<'_>, VerboseError<&str>> {
+fn unit_descriptor(
+    input: &str,
+    name: Name,
+) -> IResult<&str, MetricDescriptor<'_>, VerboseError<&str>> {
     map(
         (
-            preceded(tag("UNIT "), metric_name),
+            preceded(tag("UNIT "), name),
             preceded(char(' '), take_while(is_metric_name_char)),
         ),
         |(metric, unit)| MetricDescriptor::unit(metric, unit),
@@ -82,14 +132,15 @@ fn unit_descriptor(input: &str) -> IResult<&str, MetricDescriptor<'_>, VerboseEr
 
 #[cfg(test)]
 mod test {
-    use crate::{test::parse, MetricDescriptor, MetricType};
+    use crate::{test::parse, Limits, MetricDescriptor, MetricType};
     use rstest::rstest;
 
     #[test]
     fn help_descriptor() {
         let input = "HELP adsb_aircraft_mlat_recent Number of aircraft observed with a position determined by multilateration in the last minute";
 
-        let (rest, descriptor) = super::help_descriptor(input).unwrap();
+        let (rest, descriptor) =
+            super::help_descriptor(input, super::bare_metric_name, &Limits::default()).unwrap();
 
         let expected =
             MetricDescriptor::help(
@@ -123,6 +174,7 @@ mod test {
     #[case("counter", MetricType::Counter)]
     #[case("gauge", MetricType::Gauge)]
     #[case("gaugehistogram", MetricType::Gaugehistogram)]
+    #[case("histogram", MetricType::Histogram)]
     #[case("junk", MetricType::Unknown("junk"))]
     fn metric_descriptor_type(#[case] input_type: &str, #[case] expected: MetricType) {
         let expected = MetricDescriptor::r#type("metric", expected);
@@ -136,6 +188,28 @@ mod test {
         assert!(rest.is_empty(), "leftover: {rest:?}");
     }
 
+    #[rstest]
+    #[case(
+        "# TYPE \"my.metric\" counter\n",
+        MetricDescriptor::r#type("my.metric", MetricType::Counter)
+    )]
+    #[case(
+        "# TYPE \"my\\\"metric\" counter\n",
+        MetricDescriptor::r#type("my\"metric", MetricType::Counter)
+    )]
+    #[case("# HELP \"my.metric\" help\n", MetricDescriptor::help("my.metric", "help".into()))]
+    #[case(
+        "# UNIT my_metric seconds\n",
+        MetricDescriptor::unit("my_metric", "seconds")
+    )]
+    fn quoted_metric_descriptor(#[case] input: &str, #[case] expected: MetricDescriptor) {
+        let (rest, descriptor) = parse(super::quoted_metric_descriptor, input);
+
+        assert_eq!(expected, descriptor);
+        assert_eq!(input.trim_end(), descriptor.to_string());
+        assert!(rest.is_empty(), "leftover: {rest:?}");
+    }
+
     #[test]
     fn metric_descriptor_unit() {
         let expected = MetricDescriptor::unit("metric", "unit");
@@ -7,7 +7,7 @@ use nom::{
 };
 use nom_language::error::VerboseError;
 
-fn is_metric_name_initial_char(c: char) -> bool {
+pub(crate) fn is_metric_name_initial_char(c: char) -> bool {
     c.is_ascii_alphabetic() || c == '_' || c == ':'
 }
 
@@ -15,6 +15,13 @@ pub(crate) fn is_metric_name_char(c: char) -> bool {
     c.is_ascii_alphanumeric() || c == '_' || c == ':'
 }
 
+/// Returns `true` if `name` is a metric name that does not need quotes
+pub(crate) fn is_metric_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars.next().is_some_and(is_metric_name_initial_char) && chars.all(is_metric_name_char)
+}
+
 /// Parse a metric name: `[a-zA-Z_:][a-zA-Z0-9_:]*`
 pub(crate) fn metric_name(input: &str) -> IResult<&str, &str, VerboseError<&str>> {
     context(
@@ -0,0 +1,226 @@
+use crate::{native_histogram::is_valid_schema, parser::number::number, NativeHistogram, Span};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, i32, u32},
+    combinator::{map, map_res},
+    error::context,
+    multi::{separated_list0, separated_list1},
+    sequence::{delimited, preceded, separated_pair},
+    IResult, Parser,
+};
+use nom_language::error::VerboseError;
+
+/// The largest bucket index magnitude in spans, well beyond the `f64` range at any schema
+const MAX_INDEX: i64 = 1 << 20;
+
+/// A field of a native histogram composite value
+enum Field {
+    Count(f64),
+    Sum(f64),
+    Schema(i32),
+    ZeroThreshold(f64),
+    ZeroCount(f64),
+    Spans(Sign, Vec<Span>),
+    Deltas(Sign, Vec<f64>),
+    Buckets(Sign, Vec<f64>),
+}
+
+#[derive(Clone, Copy)]
+enum Sign {
+    Positive,
+    Negative,
+}
+
+/// Parse a native histogram composite value like
+/// `{count:12,sum:100.5,schema:3,zero_threshold:1e-128,zero_count:0,positive_spans:[0:2],positive_deltas:[2,1]}`
+///
+/// Bucket counts are given as `_deltas` from the previous bucket or as absolute `_buckets`.
+pub(crate) fn native_histogram(input: &str) -> IResult<&str, NativeHistogram, VerboseError<&str>> {
+    context(
+        "native histogram",
+        map_res(
+            delimited(char('{'), separated_list1(char(','), field), char('}')),
+            build,
+        ),
+    )
+    .parse(input)
+}
+
+fn build(fields: Vec<Field>) -> Result<NativeHistogram, &'static str> {
+    let mut histogram = NativeHistogram::default();
+    let (mut count, mut sum, mut schema) = (false, false, false);
+
+    for field in fields {
+        match field {
+            Field::Count(value) => (histogram.count, count) = (value, true),
+            Field::Sum(value) => (histogram.sum, sum) = (value, true),
+            Field::Schema(value) => (histogram.schema, schema) = (value, true),
+            Field::ZeroThreshold(value) => histogram.zero_threshold = value,
+            Field::ZeroCount(value) => histogram.zero_count = value,
+            Field::Spans(sign, spans) => *histogram.spans_mut(sign) = spans,
+            Field::Deltas(sign, deltas) => {
+                *histogram.buckets_mut(sign) = NativeHistogram::expand_deltas(&deltas);
+            }
+            Field::Buckets(sign, buckets) => *histogram.buckets_mut(sign) = buckets,
+        }
+    }
+
+    if !(count && sum && schema) {
+        return Err("native histogram requires count, sum, and schema");
+    }
+
+    if !is_valid_schema(histogram.schema) {
+        return Err("native histogram schema must be from -4 to 8");
+    }
+
+    let in_range = |spans: &[Span]| {
+        let mut next = 0;
+
+        spans.iter().all(|span| {
+            let start = next + i64::from(span.offset);
+            next = start + i64::from(span.length);
+
+            -MAX_INDEX <= start && next <= MAX_INDEX + 1
+        })
+    };
+
+    if !in_range(&histogram.positive_spans) || !in_range(&histogram.negative_spans) {
+        return Err("native histogram bucket indexes must be within ±2^20");
+    }
+
+    let length = |spans: &[Span]| spans.iter().map(|span| span.length as usize).sum::<usize>();
+
+    if length(&histogram.positive_spans) != histogram.positive_buckets.len()
+        || length(&histogram.negative_spans) != histogram.negative_buckets.len()
+    {
+        return Err("native histogram spans do not match buckets");
+    }
+
+    Ok(histogram)
+}
+
+impl NativeHistogram {
+    fn buckets_mut(&mut self, sign: Sign) -> &mut Vec<f64> {
+        match sign {
+            Sign::Positive => &mut self.positive_buckets,
+            Sign::Negative => &mut self.negative_buckets,
+        }
+    }
+
+    fn spans_mut(&mut self, sign: Sign) -> &mut Vec<Span> {
+        match sign {
+            Sign::Positive => &mut self.positive_spans,
+            Sign::Negative => &mut self.negative_spans,
+        }
+    }
+}
+
+fn field(input: &str) -> IResult<&str, Field, VerboseError<&str>> {
+    context(
+        "native histogram field",
+        alt((
+            map(preceded(tag("count:"), number), Field::Count),
+            map(preceded(tag("sum:"), number), Field::Sum),
+            map(preceded(tag("schema:"), i32), Field::Schema),
+            map(
+                preceded(tag("zero_threshold:"), number),
+                Field::ZeroThreshold,
+            ),
+            map(preceded(tag("zero_count:"), number), Field::ZeroCount),
+            map(preceded(tag("positive_spans:"), spans), |spans| {
+                Field::Spans(Sign::Positive, spans)
+            }),
+            map(preceded(tag("negative_spans:"), spans), |spans| {
+                Field::Spans(Sign::Negative, spans)
+            }),
+            map(preceded(tag("positive_deltas:"), numbers), |deltas| {
+                Field::Deltas(Sign::Positive, deltas)
+            }),
+            map(preceded(tag("negative_deltas:"), numbers), |deltas| {
+                Field::Deltas(Sign::Negative, deltas)
+            }),
+            map(preceded(tag("positive_buckets:"), numbers), |buckets| {
+                Field::Buckets(Sign::Positive, buckets)
+            }),
+            map(preceded(tag("negative_buckets:"), numbers), |buckets| {
+                Field::Buckets(Sign::Negative, buckets)
+            }),
+        )),
+    )
+    .parse(input)
+}
+
+fn numbers(input: &str) -> IResult<&str, Vec<f64>, VerboseError<&str>> {
+    delimited(char('['), separated_list0(char(','), number), char(']')).parse(input)
+}
+
+fn spans(input: &str) -> IResult<&str, Vec<Span>, VerboseError<&str>> {
+    delimited(
+        char('['),
+        separated_list0(
+            char(','),
+            map(separated_pair(i32, char(':'), u32), |(offset, length)| {
+                Span::new(offset, length)
+            }),
+        ),
+        char(']'),
+    )
+    .parse(input)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::parse;
+    use rstest::rstest;
+
+    #[test]
+    fn native_histogram() {
+        let input = "{count:12,sum:100.5,schema:3,zero_threshold:1e-128,zero_count:0,positive_spans:[0:2],positive_deltas:[2,1]}";
+
+        let (rest, histogram) = parse(super::native_histogram, input);
+
+        assert!(rest.is_empty(), "leftover: {rest:?}");
+        assert_eq!(
+            NativeHistogram {
+                count: 12.0,
+                sum: 100.5,
+                schema: 3,
+                zero_threshold: 1e-128,
+                zero_count: 0.0,
+                positive_spans: vec![Span::new(0, 2)],
+                positive_buckets: vec![2.0, 3.0],
+                ..NativeHistogram::default()
+            },
+            histogram
+        );
+        assert_eq!(input, histogram.to_string());
+    }
+
+    #[test]
+    fn native_histogram_negative_buckets() {
+        let input =
+            "{count:3.5,sum:-2,schema:-1,negative_spans:[-1:1,2:2],negative_buckets:[0.5,1,2]}";
+
+        let (_, histogram) = parse(super::native_histogram, input);
+
+        assert_eq!(
+            vec![Span::new(-1, 1), Span::new(2, 2)],
+            histogram.negative_spans
+        );
+        assert_eq!(vec![0.5, 1.0, 2.0], histogram.negative_buckets);
+    }
+
+    #[rstest]
+    #[case("{count:1,sum:1}")]
+    #[case("{count:1,sum:1,schema:9}")]
+    #[case("{count:1,sum:1,schema:0,positive_spans:[0:2],positive_deltas:[1]}")]
+    #[case("{count:1,sum:1,schema:0,bogus:1}")]
+    #[case("{count:1,sum:1,schema:0,positive_spans:[2147483647:1],positive_buckets:[1]}")]
+    #[case("{count:1,sum:1,schema:0,negative_spans:[0:1,1048576:1],negative_buckets:[1,1]}")]
+    #[case("{}")]
+    fn native_histogram_invalid(#[case] input: &str) {
+        assert!(super::native_histogram(input).is_err(), "{input:?}");
+    }
+}
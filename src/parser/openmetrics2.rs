@@ -0,0 +1,198 @@
+use crate::{
+    parser::{
+        label::{label_list, label_value, metric_label},
+        lift,
+        metric_name::metric_name as bare_metric_name,
+        number::number,
+        string, Error, SampleValue,
+    },
+    Label, Limits,
+};
+use nom::{
+    branch::alt,
+    bytes::complete::{is_not, tag},
+    character::complete::char,
+    combinator::{map, opt, verify},
+    error::{context, ErrorKind, ParseError},
+    sequence::{delimited, preceded},
+    IResult, Parser,
+};
+use nom_language::error::VerboseError;
+use std::borrow::Cow;
+
+/// The parts of an OpenMetrics 2.0 sample line
+pub(crate) struct Parts<'a> {
+    pub(crate) name: Cow<'a, str>,
+    pub(crate) value: SampleValue,
+    pub(crate) timestamp: Option<f64>,
+    pub(crate) start_timestamp: Option<f64>,
+}
+
+/// Parse a metric name which may be quoted, as in a descriptor
+///
+/// A quoted name is borrowed unless it has escapes.
+pub(crate) fn metric_name(input: &str) -> IResult<&str, Cow<'_, str>, VerboseError<&str>> {
+    context(
+        "metric name",
+        alt((map(bare_metric_name, Cow::Borrowed), quoted)),
+    )
+    .parse(input)
+}
+
+/// Parse a sample line without the trailing newline
+///
+/// A quoted metric name goes first inside the braces: `{"my.metric",label="value"} 1`.  The
+/// value may be followed by a timestamp and a `st@` start timestamp.  Labels are appended to
+/// `labels` within `limits`.
+pub(crate) fn sample<'a>(
+    input: &'a str,
+    labels: &mut Vec<Label<'a>>,
+    limits: &Limits,
+) -> IResult<&'a str, Parts<'a>, Error<&'a str>> {
+    let (rest, name) = name_and_labels(input, labels, limits)?;
+
+    let (rest, (value, timestamp, start_timestamp)) = lift(
+        (
+            preceded(char(' '), super::sample_value),
+            opt(preceded(char(' '), timestamp)),
+            opt(preceded(tag(" st@"), timestamp)),
+        )
+            .parse(rest),
+    )?;
+
+    let parts = Parts {
+        name,
+        value,
+        timestamp,
+        start_timestamp,
+    };
+
+    Ok((rest, parts))
+}
+
+/// Parse a metric name and its labels, appending the labels to `labels`
+fn name_and_labels<'a>(
+    input: &'a str,
+    labels: &mut Vec<Label<'a>>,
+    limits: &Limits,
+) -> IResult<&'a str, Cow<'a, str>, Error<&'a str>> {
+    let open = |input: &'a str| char::<_, Error<&str>>('{').parse(input);
+    let close = |input: &'a str| char::<_, Error<&str>>('}').parse(input);
+
+    if let Ok((rest, name)) = bare_metric_name(input) {
+        let Ok((rest, _)) = open(rest) else {
+            return Ok((rest, Cow::Borrowed(name)));
+        };
+
+        let (rest, ()) = label_list(rest, labels, limits, label)?;
+        let (rest, _) = close(rest)?;
+
+        return Ok((rest, Cow::Borrowed(name)));
+    }
+
+    let (rest, _) = open(input)?;
+    let (mut rest, name) = lift(quoted(rest))?;
+
+    if let Ok((after, _)) = char::<_, Error<&str>>(',').parse(rest) {
+        let start = labels.len();
+
+        (rest, ()) = label_list(after, labels, limits, label)?;
+
+        if labels.len() == start {
+            return Err(nom::Err::Error(Error::from_error_kind(
+                rest,
+                ErrorKind::SeparatedList,
+            )));
+        }
+    }
+
+    let (rest, _) = close(rest)?;
+
+    Ok((rest, name))
+}
+
+/// Parse a label whose name may be quoted within `limits`
+fn label<'a>(input: &'a str, limits: &Limits) -> IResult<&'a str, Label<'a>, Error<&'a str>> {
+    let (rest, name) =
+        lift(context("label", alt((map(metric_label, Cow::Borrowed), quoted))).parse(input))?;
+
+    label_value(input, rest, name, limits)
+}
+
+/// Parse a non-empty quoted name, borrowing it when it has no escapes
+fn quoted(input: &str) -> IResult<&str, Cow<'_, str>, VerboseError<&str>> {
+    context(
+        "quoted name",
+        alt((
+            map(
+                delimited(char('"'), is_not("\"\\\n"), char('"')),
+                Cow::Borrowed,
+            ),
+            map(
+                verify(string::label, |name: &str| !name.is_empty()),
+                Cow::Owned,
+            ),
+        )),
+    )
+    .parse(input)
+}
+
+/// Parse a timestamp in seconds since the Unix epoch
+fn timestamp(input: &str) -> IResult<&str, f64, VerboseError<&str>> {
+    context("timestamp", number).parse(input)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parser::verbose, test::parse};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("up", "up")]
+    #[case("\"my.metric\"", "my.metric")]
+    #[case("\"my\\\"metric\"", "my\"metric")]
+    fn metric_name(#[case] input: &str, #[case] expected: &str) {
+        let (rest, name) = parse(super::metric_name, input);
+
+        assert_eq!(expected, name);
+        assert!(rest.is_empty(), "leftover: {rest:?}");
+    }
+
+    #[rstest]
+    #[case("up", "up", vec![])]
+    #[case("up{}", "up", vec![])]
+    #[case("up{\"my.label\"=\"a\"}", "up", vec![Label::new("my.label", "a".into())])]
+    #[case("{\"my.metric\"}", "my.metric", vec![])]
+    #[case("{\"my\\\"metric\",job=\"a\"}", "my\"metric", vec![Label::new("job", "a".into())])]
+    fn name_and_labels(
+        #[case] input: &str,
+        #[case] expected_name: &str,
+        #[case] expected_labels: Vec<Label<'_>>,
+    ) {
+        let mut labels = vec![];
+
+        let (rest, name) = parse(
+            |input| super::name_and_labels(input, &mut labels, &Limits::default()).map_err(verbose),
+            input,
+        );
+
+        assert_eq!(expected_name, name);
+        assert_eq!(expected_labels, labels);
+        assert!(rest.is_empty(), "leftover: {rest:?}");
+    }
+
+    #[rstest]
+    #[case("{\"\"}")]
+    #[case("{\"my.metric\",}")]
+    #[case("up{,}")]
+    fn name_and_labels_error(#[case] input: &str) {
+        assert!(
+            !matches!(
+                super::name_and_labels(input, &mut vec![], &Limits::default()),
+                Ok(("", _))
+            ),
+            "input: {input}"
+        );
+    }
+}
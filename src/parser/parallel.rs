@@ -0,0 +1,234 @@
+use crate::{
+    parser::{
+        limits::exceeds, openmetrics_with_limits, prometheus_with_limits, verbose, visit,
+        visit::Families, Error, Version,
+    },
+    Family, Limits,
+};
+use memchr::{memmem, memrchr};
+use nom::IResult;
+use nom_language::error::VerboseError;
+use rayon::prelude::*;
+
+/// Chunks smaller than this are not worth parsing on another thread
+const MIN_CHUNK: usize = 64 * 1024;
+
+type Parsed<'a> = IResult<&'a str, Vec<Family<'a>>, Error<&'a str>>;
+
+/// Parse an OpenMetrics-format exposition in parallel
+///
+/// The result is identical to [`openmetrics()`](crate::parser::openmetrics).
+pub fn par_openmetrics(input: &str) -> IResult<&str, Vec<Family<'_>>, VerboseError<&str>> {
+    par_openmetrics_with_limits(input, &Limits::default()).map_err(verbose)
+}
+
+/// Parse an OpenMetrics-format exposition in parallel within `limits`
+///
+/// The result is identical to [`openmetrics_with_limits()`].
+pub fn par_openmetrics_with_limits<'a>(input: &'a str, limits: &Limits) -> Parsed<'a> {
+    parse(input, limits, openmetrics_with_limits)
+}
+
+/// Parse a Prometheus-format exposition in parallel
+///
+/// The result is identical to [`prometheus()`](crate::parser::prometheus).
+pub fn par_prometheus(input: &str) -> IResult<&str, Vec<Family<'_>>, VerboseError<&str>> {
+    par_prometheus_with_limits(input, &Limits::default()).map_err(verbose)
+}
+
+/// Parse a Prometheus-format exposition in parallel within `limits`
+///
+/// The result is identical to [`prometheus_with_limits()`].
+pub fn par_prometheus_with_limits<'a>(input: &'a str, limits: &Limits) -> Parsed<'a> {
+    parse(input, limits, prometheus_with_limits)
+}
+
+/// Parse chunks of `input` in parallel, falling back to `serial` when any chunk fails
+///
+/// The last chunk is parsed with `serial` to check the end of the exposition.  The samples and
+/// families limits apply to each chunk and then to the whole exposition, which is parsed again
+/// with `serial` to report where a limit was exceeded.
+fn parse<'a>(
+    input: &'a str,
+    limits: &Limits,
+    serial: fn(&'a str, &Limits) -> Parsed<'a>,
+) -> Parsed<'a> {
+    let target = (input.len() / rayon::current_num_threads()).max(MIN_CHUNK);
+    let chunks = chunks(input, target);
+
+    let Some((last, chunks)) = chunks.split_last() else {
+        return serial(input, limits);
+    };
+
+    let (parsed, last) = rayon::join(
+        || {
+            chunks
+                .par_iter()
+                .map(|chunk| families(chunk, limits))
+                .collect::<Option<Vec<_>>>()
+        },
+        || serial(last, limits),
+    );
+
+    match (parsed, last) {
+        (Some(parsed), Ok((rest, last))) => {
+            let families: Vec<_> = parsed.into_iter().flatten().chain(last).collect();
+            let samples = families.iter().map(|family| family.samples.len()).sum();
+
+            if exceeds(limits.max_families, families.len()) || exceeds(limits.max_samples, samples)
+            {
+                return serial(input, limits);
+            }
+
+            Ok((rest, families))
+        }
+        _ => serial(input, limits),
+    }
+}
+
+/// Parse all of `chunk` into families within `limits`
+fn families<'a>(chunk: &'a str, limits: &Limits) -> Option<Vec<Family<'a>>> {
+    let mut families = Families::default();
+
+    match visit::set(chunk, limits, Version::V1, &mut families) {
+        Ok(("", ())) => Some(families.into_families()),
+        _ => None,
+    }
+}
+
+/// Split `input` into chunks of at least `target` bytes at family boundaries
+fn chunks(input: &str, target: usize) -> Vec<&str> {
+    let mut chunks = vec![];
+    let mut rest = input;
+
+    while rest.len() > target {
+        let Some(at) = boundary(rest, target) else {
+            break;
+        };
+
+        let (chunk, tail) = rest.split_at(at);
+
+        chunks.push(chunk);
+        rest = tail;
+    }
+
+    chunks.push(rest);
+
+    chunks
+}
+
+/// Find the first family boundary after `from`
+///
+/// A family starts at a descriptor that follows a sample.  Changes of metric name without a
+/// descriptor do not start a new family when parsing serially, so they are not boundaries.
+fn boundary(input: &str, from: usize) -> Option<usize> {
+    let bytes = input.as_bytes();
+
+    memmem::find_iter(&bytes[from..], b"\n# ")
+        .map(|i| from + i)
+        .find(|&newline| {
+            let line = &bytes[newline + 1..];
+
+            let descriptor = line.starts_with(b"# HELP ")
+                || line.starts_with(b"# TYPE ")
+                || line.starts_with(b"# UNIT ");
+
+            let previous = memrchr(b'\n', &bytes[..newline]).map_or(0, |i| i + 1);
+
+            descriptor && previous < newline && bytes[previous] != b'#'
+        })
+        .map(|newline| newline + 1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        parser::{openmetrics, prometheus},
+        Limit,
+    };
+    use std::fmt::Write;
+
+    fn exposition(families: usize) -> String {
+        let mut exposition = String::new();
+
+        for family in 0..families {
+            writeln!(exposition, "# HELP metric_{family} help").unwrap();
+            writeln!(exposition, "# TYPE metric_{family} gauge").unwrap();
+            writeln!(exposition, "metric_{family}{{a=\"b\"}} {family}").unwrap();
+            writeln!(exposition, "other_{family} {family}").unwrap();
+        }
+
+        exposition
+    }
+
+    #[test]
+    fn chunks() {
+        let input = exposition(10);
+
+        let chunks = super::chunks(&input, 40);
+
+        assert_eq!(input, chunks.concat());
+        assert!(chunks.len() > 1);
+
+        for chunk in chunks {
+            assert!(chunk.starts_with("# HELP "), "chunk: {chunk:?}");
+        }
+    }
+
+    #[test]
+    fn chunks_without_boundary() {
+        let input = "# HELP up help\n# TYPE up gauge\nup 1\nup 2\n";
+
+        assert_eq!(vec![input], super::chunks(input, 1));
+    }
+
+    #[test]
+    fn par_prometheus() {
+        let input = exposition(10_000);
+
+        assert_eq!(prometheus(&input), super::par_prometheus(&input));
+    }
+
+    #[test]
+    fn par_openmetrics() {
+        let input = format!("{}# EOF\n", exposition(10_000));
+
+        assert_eq!(openmetrics(&input), super::par_openmetrics(&input));
+    }
+
+    #[test]
+    fn par_prometheus_with_limits() {
+        let input = exposition(10_000);
+        let limits = Limits {
+            max_samples: Some(15_000),
+            ..Limits::default()
+        };
+
+        let Err(nom::Err::Failure(Error::Limit { limit, .. })) =
+            super::par_prometheus_with_limits(&input, &limits)
+        else {
+            unreachable!("expected limit error");
+        };
+
+        assert_eq!(Limit::Samples, limit);
+        assert_eq!(
+            prometheus_with_limits(&input, &limits),
+            super::par_prometheus_with_limits(&input, &limits)
+        );
+    }
+
+    #[test]
+    fn par_prometheus_error() {
+        let mut input = exposition(10_000);
+        let at = input.len() / 3;
+        let at = at + input[at..].find("# HELP").unwrap();
+
+        input.insert_str(at, "# HELP broken\n");
+
+        let serial = prometheus(&input);
+
+        assert!(serial.is_err());
+        assert_eq!(serial, super::par_prometheus(&input));
+    }
+}
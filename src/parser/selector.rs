@@ -0,0 +1,151 @@
+use crate::{
+    parser::{label::metric_label, metric_name::metric_name, string},
+    LabelMatcher, MatchOp, Selector,
+};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, multispace0},
+    combinator::{map, map_res, opt, verify},
+    error::context,
+    multi::separated_list1,
+    sequence::{delimited, preceded, terminated},
+    IResult, Parser,
+};
+use nom_language::error::VerboseError;
+
+/// Parse a PromQL-style instant vector selector
+///
+/// A selector has a metric name, label matchers in braces, or both.  Matchers compare with `=`,
+/// `!=`, `=~`, or `!~`.  Regular expressions must match the entire label value.
+///
+/// ```
+/// use nom_openmetrics::{parser::{prometheus, selector}, Exposition};
+///
+/// let input = "http_requests_total{code=\"200\"} 1027\nhttp_requests_total{code=\"503\"} 3\n";
+/// let exposition = Exposition::from(prometheus(input).unwrap().1);
+///
+/// let (_, selector) = selector("http_requests_total{code=~\"5..\"}").unwrap();
+///
+/// let selected: Vec<_> = exposition.select(&selector).collect();
+///
+/// assert_eq!(1, selected.len());
+/// assert_eq!(3.0, selected[0].number());
+/// ```
+pub fn selector(input: &str) -> IResult<&str, Selector, VerboseError<&str>> {
+    let mut parser = context(
+        "selector",
+        verify(
+            delimited(
+                multispace0,
+                (
+                    opt(terminated(metric_name, multispace0)),
+                    opt(terminated(matchers, multispace0)),
+                ),
+                multispace0,
+            ),
+            |(name, matchers)| name.is_some() || matchers.is_some(),
+        ),
+    );
+
+    let (input, (name, matchers)) = parser.parse(input)?;
+
+    let selector = matchers
+        .unwrap_or_default()
+        .into_iter()
+        .fold(Selector::new(name), Selector::with_matcher);
+
+    Ok((input, selector))
+}
+
+fn matchers(input: &str) -> IResult<&str, Vec<LabelMatcher>, VerboseError<&str>> {
+    context(
+        "label matchers",
+        delimited(
+            terminated(char('{'), multispace0),
+            map(
+                opt(terminated(
+                    separated_list1(delimited(multispace0, char(','), multispace0), matcher),
+                    opt(preceded(multispace0, char(','))),
+                )),
+                Option::unwrap_or_default,
+            ),
+            preceded(multispace0, char('}')),
+        ),
+    )
+    .parse(input)
+}
+
+fn matcher(input: &str) -> IResult<&str, LabelMatcher, VerboseError<&str>> {
+    context(
+        "label matcher",
+        map_res(
+            (
+                metric_label,
+                delimited(
+                    multispace0,
+                    alt((tag("=~"), tag("!~"), tag("!="), tag("="))),
+                    multispace0,
+                ),
+                string::label,
+            ),
+            |(name, op, value)| {
+                let op = match op {
+                    "=" => MatchOp::Equal(value),
+                    "!=" => MatchOp::NotEqual(value),
+                    "=~" => MatchOp::regex(&value)?,
+                    _ => MatchOp::not_regex(&value)?,
+                };
+
+                Ok::<_, regex::Error>(LabelMatcher::new(name, op))
+            },
+        ),
+    )
+    .parse(input)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{test::parse, Sample};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("up", Some("up"), 0)]
+    #[case("up{}", Some("up"), 0)]
+    #[case("{job=\"api\"}", None, 1)]
+    #[case(" up { job = \"api\" , code=~\"5..\", } ", Some("up"), 2)]
+    #[case(
+        "http_requests_total{job=\"api\",code=~\"5..\",method!=\"GET\"}",
+        Some("http_requests_total"),
+        3
+    )]
+    #[case("{__name__=~\"up|down\",env!~\"dev.*\"}", None, 2)]
+    fn selector(#[case] input: &str, #[case] name: Option<&str>, #[case] matchers: usize) {
+        let (rest, selector) = parse(super::selector, input);
+
+        assert!(rest.is_empty(), "leftover: {rest:?}");
+        assert_eq!(name, selector.name());
+        assert_eq!(matchers, selector.matchers().len());
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("{job}")]
+    #[case("{job=api}")]
+    #[case("{job=~\"(\"}")]
+    fn selector_error(#[case] input: &str) {
+        let result = super::selector(input);
+
+        assert!(
+            !matches!(result, Ok(("", _))),
+            "{input:?} parsed: {result:?}"
+        );
+    }
+
+    #[test]
+    fn selector_escaped() {
+        let (_, selector) = parse(super::selector, "{path=\"C:\\\\\"}");
+
+        assert!(selector.matches(&Sample::new("files", 1.0).add_label("path", "C:\\")));
+    }
+}
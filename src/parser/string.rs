@@ -1,3 +1,4 @@
+use crate::{parser::Error, Limit};
 use nom::{
     branch::alt,
     bytes::complete::is_not,
@@ -63,6 +64,56 @@ pub(crate) fn label(input: &str) -> IResult<&str, String, VerboseError<&str>> {
     .parse(input)
 }
 
+/// Parse a descriptor string of at most `max` bytes, checking its length before unescaping it
+pub(crate) fn descriptor_within(
+    input: &str,
+    max: Option<usize>,
+    limit: Limit,
+) -> IResult<&str, String, Error<&str>> {
+    if max.is_some_and(|max| exceeds(input, None, max)) {
+        return Err(Error::limit(input, limit));
+    }
+
+    descriptor(input).map_err(|e| e.map(Error::Parse))
+}
+
+/// Parse a label string of at most `max` bytes, checking its length before unescaping it
+pub(crate) fn label_within(
+    input: &str,
+    max: Option<usize>,
+    limit: Limit,
+) -> IResult<&str, String, Error<&str>> {
+    let body = input.strip_prefix('"').unwrap_or(input);
+
+    if max.is_some_and(|max| exceeds(body, Some(b'"'), max)) {
+        return Err(Error::limit(input, limit));
+    }
+
+    label(input).map_err(|e| e.map(Error::Parse))
+}
+
+/// Returns `true` if `input` up to `end` or a newline is longer than `max` bytes once unescaped
+///
+/// At most `max + 1` unescaped bytes are examined.
+fn exceeds(input: &str, end: Option<u8>, max: usize) -> bool {
+    let bytes = input.as_bytes();
+    let mut i = 0;
+    let mut len = 0;
+
+    while len <= max {
+        match bytes.get(i) {
+            None | Some(b'\n') => break,
+            Some(&b) if Some(b) == end => break,
+            Some(b'\\') => i += 2,
+            Some(_) => i += 1,
+        }
+
+        len += 1;
+    }
+
+    len > max
+}
+
 fn escaped(input: &str) -> IResult<&str, Fragment<'_>, VerboseError<&str>> {
     preceded(
         char('\\'),
@@ -136,6 +187,27 @@ mod test {
         assert!(rest.is_empty(), "leftover: {rest:?}");
     }
 
+    #[rstest]
+    #[case(r#""abc""#, Some(3), true)]
+    #[case(r#""abcd""#, Some(3), false)]
+    #[case(r#""a\"\n""#, Some(3), true)]
+    #[case(r#""a\"\nb""#, Some(3), false)]
+    #[case(r#""☃""#, Some(3), true)]
+    #[case(r#""\☃""#, Some(3), true)]
+    #[case(r#""\☃a""#, Some(3), false)]
+    #[case(r#""abcd""#, None, true)]
+    fn label_within(#[case] input: &str, #[case] max: Option<usize>, #[case] within: bool) {
+        let result = super::label_within(input, max, Limit::LabelValueLength);
+
+        match result {
+            Err(nom::Err::Failure(Error::Limit { limit, .. })) => {
+                assert!(!within, "input: {input}");
+                assert_eq!(Limit::LabelValueLength, limit);
+            }
+            result => assert!(within && result.is_ok(), "input: {input} {result:?}"),
+        }
+    }
+
     #[rstest]
     #[case(r#""hello world!""#, "hello world!")]
     #[case(r#""\n""#, "\n")]
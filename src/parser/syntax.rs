@@ -0,0 +1,121 @@
+use crate::{
+    parser::{
+        lift, limits, metric_descriptor::descriptor_within, sample_into, verbose, Error, Version,
+    },
+    syntax::{Line, LineKind},
+    Limits,
+};
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_till, take_while, take_while1},
+    character::complete::char,
+    combinator::{consumed, eof, map},
+    error::context,
+    sequence::{preceded, terminated},
+    IResult, Parser,
+};
+use nom_language::error::VerboseError;
+
+fn is_blank(c: char) -> bool {
+    c == ' ' || c == '\t' || c == '\r'
+}
+
+/// Parse any line of an exposition, keeping its original text
+pub(crate) fn line(input: &str) -> IResult<&str, Line<'_>, VerboseError<&str>> {
+    line_within(input, &Limits::default()).map_err(verbose)
+}
+
+/// Parse any line of an exposition within `limits`, keeping its original text
+pub(crate) fn line_with_limits<'a>(
+    input: &'a str,
+    limits: &Limits,
+) -> IResult<&'a str, Line<'a>, Error<&'a str>> {
+    limits::line(input, limits)?;
+
+    line_within(input, limits)
+}
+
+/// Parse any line of an exposition, enforcing `limits` on descriptors and samples
+fn line_within<'a>(input: &'a str, limits: &Limits) -> IResult<&'a str, Line<'a>, Error<&'a str>> {
+    context(
+        "line",
+        map(
+            consumed(alt((
+                map(
+                    |input| descriptor_within(input, limits, Version::V1),
+                    LineKind::Descriptor,
+                ),
+                map(
+                    |input| sample_into(input, &mut vec![], Version::V1, limits),
+                    LineKind::Sample,
+                ),
+                |input| lift(eof_line(input)),
+                |input| lift(comment(input)),
+                |input| lift(blank(input)),
+            ))),
+            |(text, kind)| Line::parsed(text, kind),
+        ),
+    )
+    .parse(input)
+}
+
+fn blank(input: &str) -> IResult<&str, LineKind<'_>, VerboseError<&str>> {
+    map(
+        alt((
+            terminated(take_while(is_blank), char('\n')),
+            terminated(take_while1(is_blank), eof),
+        )),
+        LineKind::Blank,
+    )
+    .parse(input)
+}
+
+fn comment(input: &str) -> IResult<&str, LineKind<'_>, VerboseError<&str>> {
+    map(
+        terminated(preceded(char('#'), take_till(|c| c == '\n')), end_of_line),
+        LineKind::Comment,
+    )
+    .parse(input)
+}
+
+fn end_of_line(input: &str) -> IResult<&str, &str, VerboseError<&str>> {
+    alt((tag("\n"), eof)).parse(input)
+}
+
+fn eof_line(input: &str) -> IResult<&str, LineKind<'_>, VerboseError<&str>> {
+    map(terminated(tag("# EOF"), end_of_line), |_| LineKind::Eof).parse(input)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{test::parse, MetricDescriptor, Sample};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("# HELP up help\n", LineKind::Descriptor(MetricDescriptor::help("up", "help".into())))]
+    #[case("up 1\n", LineKind::Sample(Sample::new("up", 1.0)))]
+    #[case("# EOF", LineKind::Eof)]
+    #[case("# EOF\n", LineKind::Eof)]
+    #[case("# EOFS\n", LineKind::Comment(" EOFS"))]
+    #[case("# comment\n", LineKind::Comment(" comment"))]
+    #[case("#\n", LineKind::Comment(""))]
+    #[case("# HELP\n", LineKind::Comment(" HELP"))]
+    #[case("\n", LineKind::Blank(""))]
+    #[case(" \t\n", LineKind::Blank(" \t"))]
+    #[case("  ", LineKind::Blank("  "))]
+    fn line(#[case] input: &str, #[case] expected: LineKind) {
+        let (rest, line) = parse(super::line, input);
+
+        assert_eq!(&expected, line.kind());
+        assert_eq!(input, line.text());
+        assert!(rest.is_empty(), "leftover: {rest:?}");
+    }
+
+    #[test]
+    fn line_error() {
+        let input = "up{job=\"prometheus\"\n";
+
+        assert!(super::line(input).is_err());
+    }
+}
@@ -0,0 +1,286 @@
+use crate::{
+    parser::{fast, limits, Error, Version},
+    Family, Limit, Limits, MetricDescriptor, MetricType, Sample, Visitor,
+};
+use memchr::memchr;
+use nom::{
+    error::{ContextError, ErrorKind, ParseError},
+    IResult,
+};
+
+/// Parse a set of families, passing their contents to `visitor`
+///
+/// Parsing stops at the start of the first family that does not parse.  Its descriptors may
+/// already have been passed to `visitor`.  A native histogram value fails unless the family TYPE
+/// is histogram or gaugehistogram.
+pub(crate) fn set<'a, V: Visitor<'a>>(
+    mut input: &'a str,
+    limits: &Limits,
+    version: Version,
+    visitor: &mut V,
+) -> IResult<&'a str, (), Error<&'a str>> {
+    let mut families = 0;
+    let mut samples = 0;
+    let mut labels = vec![];
+
+    loop {
+        let start = input;
+        let mut started = false;
+        let mut native = false;
+
+        loop {
+            match limits::descriptor(input, limits, version) {
+                Ok((rest, descriptor)) => {
+                    if !started {
+                        start_family(start, limits, &mut families)?;
+                        started = true;
+                    }
+
+                    if let MetricDescriptor::Type {
+                        r#type: MetricType::Histogram | MetricType::Gaugehistogram,
+                        ..
+                    } = descriptor
+                    {
+                        native = true;
+                    }
+
+                    visitor.on_descriptor(descriptor);
+                    input = rest;
+                }
+                Err(nom::Err::Error(_)) => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        let mut family_samples = 0;
+
+        loop {
+            if labels.capacity() == 0 {
+                labels = visitor.labels_buffer();
+            }
+
+            let parsed = match skip_sample(input, limits, visitor)? {
+                Some(rest) => Ok((rest, None)),
+                None => limits::sample(input, limits, &mut labels, version)
+                    .map(|(rest, sample)| (rest, Some(sample))),
+            };
+
+            match parsed {
+                Ok((rest, sample)) => {
+                    if !started {
+                        start_family(start, limits, &mut families)?;
+                        started = true;
+                    }
+
+                    family_samples += 1;
+                    samples += 1;
+
+                    if limits::exceeds(limits.max_samples, samples) {
+                        return Err(Error::limit(input, Limit::Samples));
+                    }
+
+                    if let Some(sample) = sample {
+                        if sample.native_histogram().is_some() && !native {
+                            return Err(unexpected_native_histogram(input));
+                        }
+
+                        visitor.on_sample(sample);
+                    }
+
+                    input = rest;
+                }
+                Err(nom::Err::Error(_)) if family_samples == 0 => return Ok((start, ())),
+                Err(nom::Err::Error(_)) => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        visitor.on_family_end();
+    }
+}
+
+/// Count a family starting at `input`, failing when there are more than `max_families`
+fn start_family<'a>(
+    input: &'a str,
+    limits: &Limits,
+    families: &mut usize,
+) -> Result<(), nom::Err<Error<&'a str>>> {
+    *families += 1;
+
+    if limits::exceeds(limits.max_families, *families) {
+        return Err(Error::limit(input, Limit::Families));
+    }
+
+    Ok(())
+}
+
+/// A native histogram sample at `input` in a family that is not a histogram
+fn unexpected_native_histogram(input: &str) -> nom::Err<Error<&str>> {
+    nom::Err::Failure(Error::add_context(
+        input,
+        "native histogram in a family that is not a histogram",
+        Error::from_error_kind(input, ErrorKind::Verify),
+    ))
+}
+
+/// Skip the sample at the start of `input` if `visitor` does not want it
+///
+/// Returns the input following the sample if it was skipped.
+fn skip_sample<'a, V: Visitor<'a>>(
+    input: &'a str,
+    limits: &Limits,
+    visitor: &mut V,
+) -> Result<Option<&'a str>, nom::Err<Error<&'a str>>> {
+    let Some(name) = fast::metric_name(input) else {
+        return Ok(None);
+    };
+
+    if visitor.wants_sample(name) {
+        return Ok(None);
+    }
+
+    limits::line(input, limits)?;
+
+    Ok(memchr(b'\n', input.as_bytes()).map(|end| &input[end + 1..]))
+}
+
+/// A [`Visitor`] that collects [`Family`]s
+#[derive(Default)]
+pub(crate) struct Families<'a> {
+    families: Vec<Family<'a>>,
+    descriptors: Vec<MetricDescriptor<'a>>,
+    samples: Vec<Sample<'a>>,
+}
+
+impl<'a> Families<'a> {
+    /// The collected families
+    pub(crate) fn into_families(self) -> Vec<Family<'a>> {
+        self.families
+    }
+}
+
+impl<'a> Visitor<'a> for Families<'a> {
+    fn on_descriptor(&mut self, descriptor: MetricDescriptor<'a>) {
+        self.descriptors.push(descriptor);
+    }
+
+    fn on_sample(&mut self, sample: Sample<'a>) {
+        self.samples.push(sample);
+    }
+
+    fn on_family_end(&mut self) {
+        self.families.push(Family::new(
+            std::mem::take(&mut self.descriptors),
+            std::mem::take(&mut self.samples),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Events(Vec<String>);
+
+    impl<'a> Visitor<'a> for Events {
+        fn on_descriptor(&mut self, descriptor: MetricDescriptor<'a>) {
+            self.0.push(descriptor.to_string());
+        }
+
+        fn on_sample(&mut self, sample: Sample<'a>) {
+            self.0.push(sample.to_string());
+        }
+
+        fn on_family_end(&mut self) {
+            self.0.push("end".into());
+        }
+    }
+
+    #[test]
+    fn set() {
+        let input = "# TYPE up gauge\nup 1\n# TYPE requests counter\nrequests 1027\nrequests_created 10\n# EOF\n";
+        let mut events = Events::default();
+
+        let (rest, _) = super::set(input, &Limits::default(), Version::V1, &mut events).unwrap();
+
+        assert_eq!("# EOF\n", rest);
+        assert_eq!(
+            vec![
+                "# TYPE up gauge",
+                "up 1",
+                "end",
+                "# TYPE requests counter",
+                "requests 1027",
+                "requests_created 10",
+                "end"
+            ],
+            events.0
+        );
+    }
+
+    #[test]
+    fn set_incomplete_family() {
+        let input = "up 1\n# TYPE requests counter\n";
+        let mut events = Events::default();
+
+        let (rest, _) = super::set(input, &Limits::default(), Version::V1, &mut events).unwrap();
+
+        assert_eq!("# TYPE requests counter\n", rest);
+        assert_eq!(vec!["up 1", "end", "# TYPE requests counter"], events.0);
+    }
+
+    #[rstest]
+    #[case("# TYPE g gauge\ng {count:1,sum:1,schema:0}\n")]
+    #[case("g {count:1,sum:1,schema:0}\n")]
+    #[case("# TYPE h summary\nh {count:1,sum:1,schema:0}\n")]
+    fn set_native_histogram_not_histogram(#[case] input: &str) {
+        let result = super::set(
+            input,
+            &Limits::default(),
+            Version::V1,
+            &mut Events::default(),
+        );
+
+        assert!(
+            matches!(result, Err(nom::Err::Failure(Error::Parse(_)))),
+            "{result:?}"
+        );
+    }
+
+    #[rstest]
+    #[case("# TYPE h histogram\nh {count:1,sum:1,schema:0}\n")]
+    #[case("# TYPE h gaugehistogram\nh {count:1,sum:1,schema:0}\n")]
+    fn set_native_histogram(#[case] input: &str) {
+        let (rest, ()) = super::set(
+            input,
+            &Limits::default(),
+            Version::V1,
+            &mut Events::default(),
+        )
+        .unwrap();
+
+        assert!(rest.is_empty(), "leftover: {rest:?}");
+    }
+
+    #[test]
+    fn families() {
+        let input = "# TYPE up gauge\nup 1\nup 0\nrequests 1027\n";
+        let mut families = Families::default();
+
+        super::set(input, &Limits::default(), Version::V1, &mut families).unwrap();
+
+        assert_eq!(
+            vec![Family::new(
+                vec![MetricDescriptor::r#type("up", MetricType::Gauge)],
+                vec![
+                    Sample::new("up", 1.0),
+                    Sample::new("up", 0.0),
+                    Sample::new("requests", 1027.0)
+                ]
+            )],
+            families.into_families()
+        );
+    }
+}
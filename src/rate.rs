@@ -0,0 +1,250 @@
+//! # Counter rate and increase between two scrapes
+//!
+//! Use [`increases()`] to compare the counters of two [`Exposition`]s of the same target.  The
+//! increase of each counter series uses Prometheus counter reset handling: when a counter
+//! decreases it was reset and the whole current value is the increase.  When a counter has a
+//! `_created` sample a change of creation time is also a reset.
+//!
+//! Increases are not extrapolated to the edges of the interval like PromQL `rate()`.
+//!
+//! ```
+//! use nom_openmetrics::{parser::prometheus, rate::{increases, Scrape}, Exposition};
+//!
+//! let previous = "# TYPE requests counter\nrequests_total{code=\"200\"} 100\n";
+//! let previous = Exposition::from(prometheus(previous).unwrap().1);
+//!
+//! let current = "# TYPE requests counter\nrequests_total{code=\"200\"} 130\n";
+//! let current = Exposition::from(prometheus(current).unwrap().1);
+//!
+//! let increases = increases(Scrape::new(&previous, 1000.0), Scrape::new(&current, 1015.0));
+//!
+//! let increase = increases.values().next().unwrap();
+//!
+//! assert_eq!(30.0, increase.increase);
+//! assert_eq!(2.0, increase.rate);
+//! ```
+
+use crate::{Counter, Exposition, Family, Fingerprint, MetricType};
+use std::collections::HashMap;
+
+/// An [`Exposition`] and the time it was scraped
+#[derive(Clone, Copy, Debug)]
+pub struct Scrape<'e, 'a> {
+    /// The scraped exposition
+    pub exposition: &'e Exposition<'a>,
+    /// The scrape time in seconds since the Unix epoch
+    pub time: f64,
+}
+
+impl<'e, 'a> Scrape<'e, 'a> {
+    /// Create a `Scrape`
+    pub fn new(exposition: &'e Exposition<'a>, time: f64) -> Self {
+        Self { exposition, time }
+    }
+}
+
+/// The change of a counter series between two scrapes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Increase {
+    /// The increase of the counter
+    pub increase: f64,
+    /// The increase per second
+    pub rate: f64,
+    /// `true` if the counter was reset between the scrapes
+    pub reset: bool,
+}
+
+/// Compute the [`Increase`] of each counter series of `current` since `previous`
+///
+/// Series are keyed by their [`Fingerprint`] in `current`.  A series missing from `previous` is
+/// only included when its `_created` time is after the previous scrape.  The result is empty if
+/// `current` was not scraped after `previous`.
+pub fn increases(
+    previous: Scrape<'_, '_>,
+    current: Scrape<'_, '_>,
+) -> HashMap<Fingerprint, Increase> {
+    let elapsed = current.time - previous.time;
+
+    if elapsed <= 0.0 || elapsed.is_nan() {
+        return HashMap::new();
+    }
+
+    let before: HashMap<_, _> = counters(previous.exposition)
+        .map(|counter| (counter.fingerprint(), counter))
+        .collect();
+
+    counters(current.exposition)
+        .filter_map(|counter| {
+            let fingerprint = counter.fingerprint();
+            let value = counter.value;
+
+            let (increase, reset) = match before.get(&fingerprint) {
+                Some(before) => {
+                    let recreated = match (before.created, counter.created) {
+                        (Some(created_before), Some(created)) => created != created_before,
+                        _ => false,
+                    };
+                    let reset = recreated || value < before.value;
+
+                    if reset {
+                        (value, true)
+                    } else {
+                        (value - before.value, false)
+                    }
+                }
+                None => match counter.created {
+                    Some(created) if created >= previous.time => (value, false),
+                    _ => return None,
+                },
+            };
+
+            let rate = increase / elapsed;
+
+            Some((
+                fingerprint,
+                Increase {
+                    increase,
+                    rate,
+                    reset,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// The counters of `exposition`
+fn counters<'s, 'a>(exposition: &'s Exposition<'a>) -> impl Iterator<Item = Counter<'a>> + 's {
+    exposition
+        .families()
+        .iter()
+        .filter(|family| family.metric_type() == Some(&MetricType::Counter))
+        .flat_map(Family::counters)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parser::openmetrics, test::parse};
+
+    fn exposition(input: &str) -> Exposition<'_> {
+        let (_, families) = parse(openmetrics, input);
+
+        Exposition::from(families)
+    }
+
+    fn increase(previous: &str, current: &str) -> Vec<Increase> {
+        let previous = exposition(previous);
+        let current = exposition(current);
+
+        increases(Scrape::new(&previous, 100.0), Scrape::new(&current, 110.0))
+            .into_values()
+            .collect()
+    }
+
+    #[test]
+    fn increase_counter() {
+        let increases = increase(
+            "# TYPE a counter\na_total 5\n# TYPE b gauge\nb 1\n# EOF\n",
+            "# TYPE a counter\na_total 25\n# TYPE b gauge\nb 7\n# EOF\n",
+        );
+
+        assert_eq!(
+            vec![Increase {
+                increase: 20.0,
+                rate: 2.0,
+                reset: false
+            }],
+            increases
+        );
+    }
+
+    #[test]
+    fn increase_reset() {
+        let increases = increase(
+            "# TYPE a counter\na_total 50\n# EOF\n",
+            "# TYPE a counter\na_total 10\n# EOF\n",
+        );
+
+        assert_eq!(
+            vec![Increase {
+                increase: 10.0,
+                rate: 1.0,
+                reset: true
+            }],
+            increases
+        );
+    }
+
+    #[test]
+    fn increase_reset_created() {
+        let increases = increase(
+            "# TYPE a counter\na_total 5\na_created 50\n# EOF\n",
+            "# TYPE a counter\na_total 8\na_created 105\n# EOF\n",
+        );
+
+        assert_eq!(
+            vec![Increase {
+                increase: 8.0,
+                rate: 0.8,
+                reset: true
+            }],
+            increases
+        );
+    }
+
+    #[test]
+    fn increase_created_unchanged() {
+        let increases = increase(
+            "# TYPE a counter\na_total{x=\"1\"} 5\na_created{x=\"1\"} 50\n# EOF\n",
+            "# TYPE a counter\na_total{x=\"1\"} 8\na_created{x=\"1\"} 50\n# EOF\n",
+        );
+
+        assert_eq!(
+            vec![Increase {
+                increase: 3.0,
+                rate: 0.3,
+                reset: false
+            }],
+            increases
+        );
+    }
+
+    #[test]
+    fn increase_created_unchanged_decrease() {
+        let increases = increase(
+            "# TYPE a counter\na_total 50\na_created 50\n# EOF\n",
+            "# TYPE a counter\na_total 10\na_created 50\n# EOF\n",
+        );
+
+        assert_eq!(
+            vec![Increase {
+                increase: 10.0,
+                rate: 1.0,
+                reset: true
+            }],
+            increases
+        );
+    }
+
+    #[test]
+    fn increase_new_series() {
+        let increases = increase(
+            "# TYPE a counter\na_total{x=\"1\"} 5\n# EOF\n",
+            "# TYPE a counter\na_total{x=\"1\"} 5\na_total{x=\"2\"} 4\na_created{x=\"2\"} 105\na_total{x=\"3\"} 9\n# EOF\n",
+        );
+
+        let mut increases: Vec<_> = increases.iter().map(|i| i.increase).collect();
+        increases.sort_by(f64::total_cmp);
+
+        assert_eq!(vec![0.0, 4.0], increases);
+    }
+
+    #[test]
+    fn increase_not_after() {
+        let previous = exposition("# TYPE a counter\na_total 5\n# EOF\n");
+
+        let increases = increases(Scrape::new(&previous, 100.0), Scrape::new(&previous, 100.0));
+
+        assert!(increases.is_empty());
+    }
+}
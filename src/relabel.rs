@@ -0,0 +1,649 @@
+//! # Relabeling with Prometheus `relabel_config` semantics
+//!
+//! Use [`process()`] to apply a list of [`Config`]s to a [`Sample`] the way Prometheus applies
+//! `metric_relabel_configs`.  The metric name is the `__name__` label.
+//!
+//! With the `serde` feature a [`Config`] deserializes from Prometheus configuration and is
+//! [validated](Config::validate) as it is deserialized:
+//!
+//! ```
+//! # #[cfg(feature = "serde")]
+//! # fn main() {
+//! use nom_openmetrics::{relabel, Sample};
+//!
+//! let configs: Vec<relabel::Config> = serde_yaml::from_str(r#"
+//! - source_labels: [job]
+//!   regex: node
+//!   action: drop
+//! - source_labels: [instance]
+//!   regex: "(.*):\\d+"
+//!   target_label: host
+//! "#).unwrap();
+//!
+//! let mut sample = Sample::new("up", 1.0).add_label("instance", "localhost:9090");
+//!
+//! assert!(relabel::process(&mut sample, &configs));
+//! assert_eq!(Some("localhost"), sample.labels().get("host"));
+//! # }
+//! # #[cfg(not(feature = "serde"))]
+//! # fn main() {}
+//! ```
+
+use crate::{
+    parser::label::{is_metric_label_end, is_metric_label_start},
+    selector::anchored,
+    Sample,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer};
+use std::{fmt, ops::Deref};
+
+/// The label name Prometheus uses for the metric name
+const METRIC_NAME: &str = "__name__";
+
+/// Apply `configs` to `sample` in order
+///
+/// Returns `false` if `sample` was dropped.  A sample whose metric name was removed is dropped.
+/// Build `configs` with [`Config::validate()`] so an invalid config does not silently do nothing.
+pub fn process(sample: &mut Sample<'_>, configs: &[Config]) -> bool {
+    configs.iter().all(|config| config.apply(sample)) && !sample.name().is_empty()
+}
+
+/// A relabeling action
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, strum::Display)]
+#[strum(serialize_all = "lowercase")]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Action {
+    /// Set `target_label` to `replacement` when `regex` matches the source value
+    #[default]
+    Replace,
+    /// Drop samples whose source value does not match `regex`
+    Keep,
+    /// Drop samples whose source value matches `regex`
+    Drop,
+    /// Drop samples whose source value does not equal the value of `target_label`
+    KeepEqual,
+    /// Drop samples whose source value equals the value of `target_label`
+    DropEqual,
+    /// Set `target_label` to a hash of the source value modulo `modulus`
+    HashMod,
+    /// Copy labels whose names match `regex` to names built from `replacement`
+    LabelMap,
+    /// Remove labels whose names match `regex`
+    LabelDrop,
+    /// Remove labels whose names do not match `regex`
+    LabelKeep,
+    /// Set `target_label` to the lowercased source value
+    Lowercase,
+    /// Set `target_label` to the uppercased source value
+    Uppercase,
+}
+
+/// A relabeling step
+///
+/// The default values are the Prometheus defaults.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize),
+    serde(remote = "Self", default)
+)]
+pub struct Config {
+    /// Labels whose values are joined into the source value
+    pub source_labels: Vec<String>,
+    /// Separates source label values
+    pub separator: String,
+    /// Matches the source value, or label names for the `label*` actions
+    pub regex: Regex,
+    /// Modulus for [`Action::HashMod`]
+    pub modulus: u64,
+    /// The label written by the action
+    pub target_label: String,
+    /// The value written, which may refer to `regex` capture groups like `$1`
+    pub replacement: String,
+    /// The [`Action`]
+    pub action: Action,
+}
+
+impl Config {
+    /// Create a `Config` for `action` with Prometheus defaults
+    pub fn new(action: Action) -> Self {
+        Self {
+            action,
+            ..Self::default()
+        }
+    }
+
+    /// Check that this `Config` can be applied, like Prometheus does when loading configuration
+    ///
+    /// [`Action::HashMod`] requires a non-zero `modulus`, and the actions that write or compare
+    /// `target_label` require one.
+    pub fn validate(self) -> Result<Self, ConfigError> {
+        if self.action == Action::HashMod && self.modulus == 0 {
+            return Err(ConfigError::ZeroModulus);
+        }
+
+        let writes_target = matches!(
+            self.action,
+            Action::Replace
+                | Action::HashMod
+                | Action::Lowercase
+                | Action::Uppercase
+                | Action::KeepEqual
+                | Action::DropEqual
+        );
+
+        if writes_target && self.target_label.is_empty() {
+            return Err(ConfigError::MissingTargetLabel(self.action));
+        }
+
+        Ok(self)
+    }
+
+    /// Apply this `Config` to `sample`
+    ///
+    /// Returns `false` if `sample` was dropped.
+    pub fn apply(&self, sample: &mut Sample<'_>) -> bool {
+        let value = self
+            .source_labels
+            .iter()
+            .map(|name| get(sample, name))
+            .collect::<Vec<_>>()
+            .join(&self.separator);
+
+        match self.action {
+            Action::Replace => {
+                let Some(captures) = self.regex.captures(&value) else {
+                    return true;
+                };
+
+                let mut target = String::new();
+                captures.expand(&self.target_label, &mut target);
+
+                if !is_label_name(&target) {
+                    return true;
+                }
+
+                let mut replacement = String::new();
+                captures.expand(&self.replacement, &mut replacement);
+
+                if replacement.is_empty() {
+                    remove(sample, &target);
+                } else {
+                    set(sample, target, replacement);
+                }
+            }
+            Action::Keep => return self.regex.is_match(&value),
+            Action::Drop => return !self.regex.is_match(&value),
+            Action::KeepEqual => return get(sample, &self.target_label) == value,
+            Action::DropEqual => return get(sample, &self.target_label) != value,
+            Action::HashMod => {
+                let digest = md5::compute(value.as_bytes());
+                let (_, low) = digest.0.split_at(8);
+                let hash = u64::from_be_bytes(low.try_into().expect("8 bytes"));
+
+                if let Some(hash) = hash.checked_rem(self.modulus) {
+                    set(sample, self.target_label.clone(), hash.to_string());
+                }
+            }
+            Action::LabelMap => {
+                for (name, value) in pairs(sample) {
+                    if self.regex.is_match(&name) {
+                        let target = self.regex.replace_all(&name, &self.replacement);
+
+                        set(sample, target.into_owned(), value);
+                    }
+                }
+            }
+            Action::LabelDrop => self.retain(sample, false),
+            Action::LabelKeep => self.retain(sample, true),
+            Action::Lowercase => set(sample, self.target_label.clone(), value.to_lowercase()),
+            Action::Uppercase => set(sample, self.target_label.clone(), value.to_uppercase()),
+        }
+
+        true
+    }
+
+    /// Remove labels whose names match `regex` unless `keep` is `true`
+    fn retain(&self, sample: &mut Sample<'_>, keep: bool) {
+        if self.regex.is_match(METRIC_NAME) != keep {
+            sample.set_name("");
+        }
+
+        sample
+            .labels_mut()
+            .retain(|label| self.regex.is_match(&label.name) == keep);
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Config {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Config::deserialize(deserializer)?
+            .validate()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            source_labels: vec![],
+            separator: ";".into(),
+            regex: Regex::default(),
+            modulus: 0,
+            target_label: String::new(),
+            replacement: "$1".into(),
+            action: Action::default(),
+        }
+    }
+}
+
+/// An invalid [`Config`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConfigError {
+    /// [`Action::HashMod`] with a `modulus` of zero
+    ZeroModulus,
+    /// The [`Action`] requires a `target_label`
+    MissingTargetLabel(Action),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroModulus => f.write_str("relabel action hashmod requires a non-zero modulus"),
+            Self::MissingTargetLabel(action) => {
+                write!(f, "relabel action {action} requires a target_label")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// A regular expression that must match an entire value
+///
+/// Like Prometheus, `.` also matches a newline.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize), serde(try_from = "String"))]
+pub struct Regex(regex::Regex);
+
+impl Regex {
+    /// Create a `Regex` matching all of a value
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        anchored(pattern).map(Self)
+    }
+}
+
+impl Default for Regex {
+    fn default() -> Self {
+        Self::new("(.*)").expect("valid default regex")
+    }
+}
+
+impl Deref for Regex {
+    type Target = regex::Regex;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for Regex {
+    type Error = regex::Error;
+
+    fn try_from(pattern: String) -> Result<Self, Self::Error> {
+        Self::new(&pattern)
+    }
+}
+
+/// The value of label `name`, or the empty string if it is missing
+fn get<'s>(sample: &'s Sample<'_>, name: &str) -> &'s str {
+    if name == METRIC_NAME {
+        sample.name()
+    } else {
+        sample.labels().get(name).unwrap_or_default()
+    }
+}
+
+fn is_label_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars.next().is_some_and(is_metric_label_start) && chars.all(is_metric_label_end)
+}
+
+/// The names and values of all labels including the metric name
+fn pairs(sample: &Sample<'_>) -> Vec<(String, String)> {
+    let name = (METRIC_NAME.to_string(), sample.name().to_string());
+
+    std::iter::once(name)
+        .chain(
+            sample
+                .labels()
+                .iter()
+                .map(|label| (label.name.to_string(), label.value.clone())),
+        )
+        .collect()
+}
+
+fn remove(sample: &mut Sample<'_>, name: &str) {
+    if name == METRIC_NAME {
+        sample.set_name("");
+    } else {
+        sample.labels_mut().remove(name);
+    }
+}
+
+fn set(sample: &mut Sample<'_>, name: String, value: String) {
+    if name == METRIC_NAME {
+        sample.set_name(value);
+    } else {
+        sample.labels_mut().set(name, value);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Labels;
+
+    fn config(action: Action, source_labels: &[&str], regex: &str) -> Config {
+        Config {
+            source_labels: source_labels.iter().map(|name| name.to_string()).collect(),
+            regex: Regex::new(regex).unwrap(),
+            ..Config::new(action)
+        }
+    }
+
+    fn requests() -> Sample<'static> {
+        Sample::new("http_requests_total", 1.0)
+            .add_label("a", "foo")
+            .add_label("b", "bar")
+            .add_label("c", "baz")
+    }
+
+    #[test]
+    fn replace() {
+        let config = Config {
+            target_label: "d".into(),
+            replacement: "ch${1}-ch${2}".into(),
+            ..config(Action::Replace, &["a", "b"], "f(.*);(.*)r")
+        };
+        let mut sample = requests();
+
+        assert!(config.apply(&mut sample));
+        assert_eq!(Some("choo-chba"), sample.labels().get("d"));
+    }
+
+    #[test]
+    fn replace_no_match() {
+        let config = Config {
+            target_label: "d".into(),
+            ..config(Action::Replace, &["a"], "o+")
+        };
+        let mut sample = requests();
+
+        assert!(config.apply(&mut sample));
+        assert_eq!(requests(), sample);
+    }
+
+    #[test]
+    fn replace_empty_removes() {
+        let config = Config {
+            target_label: "a".into(),
+            replacement: "".into(),
+            ..config(Action::Replace, &["b"], "bar")
+        };
+        let mut sample = requests();
+
+        config.apply(&mut sample);
+
+        assert!(!sample.labels().contains("a"));
+    }
+
+    #[test]
+    fn replace_target_from_capture() {
+        let config = Config {
+            target_label: "${1}_label".into(),
+            replacement: "$2".into(),
+            ..config(Action::Replace, &["a"], "(f)(oo)")
+        };
+        let mut sample = requests();
+
+        config.apply(&mut sample);
+
+        assert_eq!(Some("oo"), sample.labels().get("f_label"));
+    }
+
+    #[test]
+    fn replace_invalid_target() {
+        let config = Config {
+            target_label: "${1}".into(),
+            ..config(Action::Replace, &["a"], "(.*)")
+        };
+        let mut sample = Sample::new("up", 1.0).add_label("a", "1x");
+
+        config.apply(&mut sample);
+
+        assert_eq!(1, sample.labels().len());
+    }
+
+    #[test]
+    fn replace_metric_name() {
+        let config = Config {
+            target_label: "__name__".into(),
+            replacement: "requests_total".into(),
+            ..config(Action::Replace, &["__name__"], "http_(.*)")
+        };
+        let mut sample = requests();
+
+        config.apply(&mut sample);
+
+        assert_eq!("requests_total", sample.name());
+    }
+
+    #[test]
+    fn keep_drop() {
+        let mut sample = requests();
+
+        assert!(config(Action::Keep, &["a"], "fo+").apply(&mut sample));
+        assert!(!config(Action::Keep, &["a"], "fo").apply(&mut sample));
+        assert!(config(Action::Drop, &["a"], "fo").apply(&mut sample));
+        assert!(!config(Action::Drop, &["a", "b"], "foo;bar").apply(&mut sample));
+        assert!(!config(Action::Drop, &["missing"], "").apply(&mut sample));
+    }
+
+    #[test]
+    fn multi_line_value() {
+        let mut sample = Sample::new("up", 1.0).add_label("a", "foo\nbar");
+
+        let copy = Config {
+            source_labels: vec!["a".into()],
+            target_label: "b".into(),
+            ..Config::new(Action::Replace)
+        };
+
+        assert!(copy.apply(&mut sample));
+        assert_eq!(Some("foo\nbar"), sample.labels().get("b"));
+        assert!(config(Action::Keep, &["a"], "foo.bar").apply(&mut sample));
+        assert!(!config(Action::Drop, &["a"], "foo.*").apply(&mut sample));
+    }
+
+    #[test]
+    fn keep_drop_equal() {
+        let mut sample = Sample::new("up", 1.0)
+            .add_label("a", "1")
+            .add_label("b", "1");
+
+        let equal = |action| Config {
+            target_label: "b".into(),
+            ..config(action, &["a"], "")
+        };
+
+        assert!(equal(Action::KeepEqual).apply(&mut sample));
+        assert!(!equal(Action::DropEqual).apply(&mut sample));
+    }
+
+    #[test]
+    fn hashmod() {
+        let config = Config {
+            target_label: "d".into(),
+            modulus: 1000,
+            ..config(Action::HashMod, &["c"], "(.*)")
+        };
+        let mut sample = requests();
+
+        config.apply(&mut sample);
+
+        assert_eq!(Some("976"), sample.labels().get("d"));
+    }
+
+    #[test]
+    fn labelmap() {
+        let config = Config {
+            replacement: "${1}_copy".into(),
+            ..config(Action::LabelMap, &[], "(a|b)")
+        };
+        let mut sample = requests();
+
+        config.apply(&mut sample);
+
+        assert_eq!(Some("foo"), sample.labels().get("a_copy"));
+        assert_eq!(Some("bar"), sample.labels().get("b_copy"));
+        assert_eq!(Some("foo"), sample.labels().get("a"));
+    }
+
+    #[test]
+    fn labeldrop_labelkeep() {
+        let mut dropped = requests();
+
+        config(Action::LabelDrop, &[], "a|b").apply(&mut dropped);
+
+        assert_eq!(&Labels::new().with("c", "baz"), dropped.labels());
+        assert_eq!("http_requests_total", dropped.name());
+
+        let mut kept = requests();
+
+        config(Action::LabelKeep, &[], "__name__|a").apply(&mut kept);
+
+        assert_eq!(&Labels::new().with("a", "foo"), kept.labels());
+        assert_eq!("http_requests_total", kept.name());
+    }
+
+    #[test]
+    fn lowercase_uppercase() {
+        let mut sample = Sample::new("up", 1.0).add_label("a", "MiXeD");
+
+        let case = |action, target: &str| Config {
+            target_label: target.into(),
+            ..config(action, &["a"], "")
+        };
+
+        case(Action::Lowercase, "lower").apply(&mut sample);
+        case(Action::Uppercase, "upper").apply(&mut sample);
+
+        assert_eq!(Some("mixed"), sample.labels().get("lower"));
+        assert_eq!(Some("MIXED"), sample.labels().get("upper"));
+    }
+
+    #[test]
+    fn validate() {
+        let hashmod = Config {
+            target_label: "d".into(),
+            ..Config::new(Action::HashMod)
+        };
+
+        assert_eq!(
+            Some(ConfigError::ZeroModulus),
+            hashmod.clone().validate().err()
+        );
+        assert!(Config {
+            modulus: 10,
+            ..hashmod
+        }
+        .validate()
+        .is_ok());
+
+        assert_eq!(
+            Some(ConfigError::MissingTargetLabel(Action::Replace)),
+            Config::new(Action::Replace).validate().err()
+        );
+        assert_eq!(
+            Some(ConfigError::MissingTargetLabel(Action::HashMod)),
+            Config {
+                modulus: 10,
+                ..Config::new(Action::HashMod)
+            }
+            .validate()
+            .err()
+        );
+        assert!(Config::new(Action::LabelDrop).validate().is_ok());
+    }
+
+    #[test]
+    fn process_drops_nameless() {
+        let mut sample = requests();
+
+        assert!(!process(
+            &mut sample,
+            &[config(Action::LabelKeep, &[], "a")]
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize() {
+        let configs: Vec<Config> = serde_yaml::from_str(
+            r#"
+- source_labels: [a, b]
+  separator: ","
+  regex: "(.*),(.*)"
+  target_label: joined
+  replacement: "$2-$1"
+- action: hashmod
+  source_labels: [c]
+  modulus: 1000
+  target_label: d
+- action: labeldrop
+  regex: b
+"#,
+        )
+        .unwrap();
+
+        let mut sample = requests();
+
+        assert!(process(&mut sample, &configs));
+        assert_eq!(
+            &Labels::new()
+                .with("a", "foo")
+                .with("c", "baz")
+                .with("joined", "bar-foo")
+                .with("d", "976"),
+            sample.labels()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_invalid() {
+        let result = serde_yaml::from_str::<Config>("action: hashmod\ntarget_label: d\n");
+
+        assert!(result.is_err());
+
+        let result = serde_yaml::from_str::<Config>("source_labels: [a]\n");
+
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_invalid_regex() {
+        let result = serde_yaml::from_str::<Config>("regex: \"(\"");
+
+        assert!(result.is_err());
+    }
+}
@@ -1,58 +1,183 @@
-use crate::Label;
+use crate::{
+    label::fmt_escaped, parser::metric_name::is_metric_name, Fingerprint, Label, Labels,
+    NativeHistogram,
+};
+use std::{borrow::Cow, fmt};
 
 /// A metric sample
 #[derive(Debug, PartialEq)]
 pub struct Sample<'a> {
-    name: &'a str,
-    labels: Vec<Label<'a>>,
+    name: Cow<'a, str>,
+    labels: Labels<'a>,
     number: f64,
+    native_histogram: Option<Box<NativeHistogram>>,
+    timestamp: Option<f64>,
+    start_timestamp: Option<f64>,
 }
 
 impl<'a> Sample<'a> {
     /// Create a `Sample` without labels
-    pub fn new(name: &'a str, number: f64) -> Self {
-        let labels = vec![];
+    pub fn new(name: impl Into<Cow<'a, str>>, number: f64) -> Self {
+        let labels = Labels::new();
 
         Self {
-            name,
+            name: name.into(),
             labels,
             number,
+            native_histogram: None,
+            timestamp: None,
+            start_timestamp: None,
         }
     }
 
     /// Create a `Sample` with labels
-    pub fn with_labels(name: &'a str, number: f64, labels: Vec<Label<'a>>) -> Self {
+    pub fn with_labels(
+        name: impl Into<Cow<'a, str>>,
+        number: f64,
+        labels: impl Into<Labels<'a>>,
+    ) -> Self {
         Self {
-            name,
-            labels,
+            name: name.into(),
+            labels: labels.into(),
             number,
+            native_histogram: None,
+            timestamp: None,
+            start_timestamp: None,
         }
     }
 
     /// Add a label to a `Sample`
-    pub fn add_label(mut self, name: &'a str, value: &str) -> Self {
-        let label = Label {
-            name,
-            value: value.into(),
-        };
+    pub fn add_label(mut self, name: impl Into<Cow<'a, str>>, value: &str) -> Self {
+        self.labels.push(name, value);
+
+        self
+    }
 
-        self.labels.push(label);
+    /// Make a `Sample` a native histogram
+    ///
+    /// The number of the sample becomes the histogram count.
+    pub fn with_native_histogram(mut self, histogram: NativeHistogram) -> Self {
+        self.number = histogram.count;
+        self.native_histogram = Some(Box::new(histogram));
 
         self
     }
 
-    /// [`Label`]s for a `Sample`
-    pub fn labels(&self) -> &[Label<'a>] {
+    /// Set the timestamp of a `Sample` in seconds since the Unix epoch
+    pub fn with_timestamp(mut self, timestamp: f64) -> Self {
+        self.timestamp = Some(timestamp);
+
+        self
+    }
+
+    /// Set the OpenMetrics 2.0 `st@` start timestamp of a `Sample`
+    pub fn with_start_timestamp(mut self, start_timestamp: f64) -> Self {
+        self.start_timestamp = Some(start_timestamp);
+
+        self
+    }
+
+    /// [`Labels`] for a `Sample`
+    pub fn labels(&self) -> &Labels<'a> {
         &self.labels
     }
 
+    /// The [`Fingerprint`] of the series of a `Sample`
+    pub fn fingerprint(&self) -> Fingerprint {
+        Fingerprint::from(self)
+    }
+
+    /// Mutable [`Labels`] for a `Sample`
+    pub fn labels_mut(&mut self) -> &mut Labels<'a> {
+        &mut self.labels
+    }
+
+    /// Consume a `Sample` returning its [`Label`]s
+    pub fn into_labels(self) -> Vec<Label<'a>> {
+        self.labels.into_vec()
+    }
+
     /// The metric name
     pub fn name(&self) -> &str {
-        self.name
+        &self.name
+    }
+
+    /// Set the metric name
+    pub fn set_name(&mut self, name: impl Into<Cow<'a, str>>) {
+        self.name = name.into();
+    }
+
+    /// The [`NativeHistogram`] value of a native histogram sample
+    pub fn native_histogram(&self) -> Option<&NativeHistogram> {
+        self.native_histogram.as_deref()
     }
 
     /// The metric value
+    ///
+    /// For a native histogram this is the count of observations.
     pub fn number(&self) -> f64 {
         self.number
     }
+
+    /// The start time of the series in seconds since the Unix epoch
+    ///
+    /// This replaces the `_created` samples of OpenMetrics 1.0.
+    pub fn start_timestamp(&self) -> Option<f64> {
+        self.start_timestamp
+    }
+
+    /// The time of the sample in seconds since the Unix epoch
+    pub fn timestamp(&self) -> Option<f64> {
+        self.timestamp
+    }
+}
+
+impl fmt::Display for Sample<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if is_metric_name(&self.name) {
+            write!(f, "{}{} ", self.name, self.labels)?;
+        } else {
+            f.write_str("{\"")?;
+            fmt_escaped(&self.name, f)?;
+            f.write_str("\"")?;
+
+            for label in self.labels.iter() {
+                write!(f, ",{label}")?;
+            }
+
+            f.write_str("} ")?;
+        }
+
+        match &self.native_histogram {
+            Some(histogram) => write!(f, "{histogram}")?,
+            None => fmt_number(self.number, f)?,
+        }
+
+        if let Some(timestamp) = self.timestamp {
+            f.write_str(" ")?;
+            fmt_number(timestamp, f)?;
+        }
+
+        if let Some(start_timestamp) = self.start_timestamp {
+            f.write_str(" st@")?;
+            fmt_number(start_timestamp, f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Write a number in exposition format
+pub(crate) fn fmt_number(number: f64, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if number.is_nan() {
+        f.write_str("NaN")
+    } else if number == f64::INFINITY {
+        f.write_str("+Inf")
+    } else if number == f64::NEG_INFINITY {
+        f.write_str("-Inf")
+    } else if number.fract() == 0.0 && number.abs() < 1e15 {
+        write!(f, "{number}")
+    } else {
+        write!(f, "{number:?}")
+    }
 }
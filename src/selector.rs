@@ -0,0 +1,186 @@
+use crate::Sample;
+use regex::Regex;
+
+/// The label name PromQL uses for the metric name
+const METRIC_NAME: &str = "__name__";
+
+/// A PromQL-style instant vector selector such as `up{job="api",code=~"5.."}`
+///
+/// Parse a selector with [`selector()`](crate::parser::selector()).
+#[derive(Clone, Debug, Default)]
+pub struct Selector {
+    name: Option<String>,
+    matchers: Vec<LabelMatcher>,
+}
+
+impl Selector {
+    /// Create a `Selector` for samples named `name`, or for all samples
+    pub fn new(name: Option<&str>) -> Self {
+        Self {
+            name: name.map(Into::into),
+            matchers: vec![],
+        }
+    }
+
+    /// The [`LabelMatcher`]s
+    pub fn matchers(&self) -> &[LabelMatcher] {
+        &self.matchers
+    }
+
+    /// Returns `true` if `sample` is selected
+    pub fn matches(&self, sample: &Sample<'_>) -> bool {
+        self.name
+            .as_deref()
+            .is_none_or(|name| name == sample.name())
+            && self.matchers.iter().all(|matcher| matcher.matches(sample))
+    }
+
+    /// The metric name
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Add a [`LabelMatcher`]
+    pub fn with_matcher(mut self, matcher: LabelMatcher) -> Self {
+        self.matchers.push(matcher);
+
+        self
+    }
+}
+
+/// Matches the value of one label
+///
+/// A missing label has an empty value.  The `__name__` label matches the metric name.
+#[derive(Clone, Debug)]
+pub struct LabelMatcher {
+    name: String,
+    op: MatchOp,
+}
+
+impl LabelMatcher {
+    /// Create a `LabelMatcher` for label `name`
+    pub fn new(name: &str, op: MatchOp) -> Self {
+        Self {
+            name: name.into(),
+            op,
+        }
+    }
+
+    /// Returns `true` if the label named `name` of `sample` matches
+    pub fn matches(&self, sample: &Sample<'_>) -> bool {
+        let value = if self.name == METRIC_NAME {
+            sample.name()
+        } else {
+            sample.labels().get(&self.name).unwrap_or_default()
+        };
+
+        self.op.matches(value)
+    }
+
+    /// The label name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The [`MatchOp`]
+    pub fn op(&self) -> &MatchOp {
+        &self.op
+    }
+}
+
+/// How a [`LabelMatcher`] compares a label value
+#[derive(Clone, Debug)]
+pub enum MatchOp {
+    /// `=`
+    Equal(String),
+    /// `!=`
+    NotEqual(String),
+    /// `=~`
+    Regex(Regex),
+    /// `!~`
+    NotRegex(Regex),
+}
+
+impl MatchOp {
+    /// Create a [`MatchOp::Regex`] matching all of a value like PromQL
+    pub fn regex(pattern: &str) -> Result<Self, regex::Error> {
+        anchored(pattern).map(Self::Regex)
+    }
+
+    /// Create a [`MatchOp::NotRegex`] matching all of a value like PromQL
+    pub fn not_regex(pattern: &str) -> Result<Self, regex::Error> {
+        anchored(pattern).map(Self::NotRegex)
+    }
+
+    /// Returns `true` if `value` matches
+    pub fn matches(&self, value: &str) -> bool {
+        match self {
+            MatchOp::Equal(expected) => value == expected,
+            MatchOp::NotEqual(expected) => value != expected,
+            MatchOp::Regex(regex) => regex.is_match(value),
+            MatchOp::NotRegex(regex) => !regex.is_match(value),
+        }
+    }
+}
+
+/// Compile `pattern` to match all of a value like Prometheus, where `.` also matches a newline
+pub(crate) fn anchored(pattern: &str) -> Result<Regex, regex::Error> {
+    Regex::new(&format!("^(?s:{pattern})$"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(MatchOp::Equal("api".into()), "api", true)]
+    #[case(MatchOp::Equal("api".into()), "apis", false)]
+    #[case(MatchOp::NotEqual("api".into()), "api", false)]
+    #[case(MatchOp::NotEqual("api".into()), "", true)]
+    #[case(MatchOp::regex("5..").unwrap(), "503", true)]
+    #[case(MatchOp::regex("5..").unwrap(), "5030", false)]
+    #[case(MatchOp::regex("a|b").unwrap(), "b", true)]
+    #[case(MatchOp::regex("a|b").unwrap(), "ab", false)]
+    #[case(MatchOp::not_regex("5..").unwrap(), "200", true)]
+    #[case(MatchOp::regex("").unwrap(), "", true)]
+    #[case(MatchOp::regex(".*").unwrap(), "a\nb", true)]
+    #[case(MatchOp::not_regex("a.b").unwrap(), "a\nb", false)]
+    fn match_op(#[case] op: MatchOp, #[case] value: &str, #[case] expected: bool) {
+        assert_eq!(expected, op.matches(value), "{op:?} {value:?}");
+    }
+
+    #[test]
+    fn matches() {
+        let selector = Selector::new(Some("http_requests_total"))
+            .with_matcher(LabelMatcher::new("job", MatchOp::Equal("api".into())))
+            .with_matcher(LabelMatcher::new("code", MatchOp::regex("5..").unwrap()))
+            .with_matcher(LabelMatcher::new("method", MatchOp::NotEqual("GET".into())));
+
+        let sample = |name, method, code| {
+            Sample::new(name, 1.0)
+                .add_label("job", "api")
+                .add_label("method", method)
+                .add_label("code", code)
+        };
+
+        assert!(selector.matches(&sample("http_requests_total", "POST", "500")));
+        assert!(!selector.matches(&sample("http_requests_total", "GET", "500")));
+        assert!(!selector.matches(&sample("http_requests_total", "POST", "200")));
+        assert!(!selector.matches(&sample("http_requests", "POST", "500")));
+    }
+
+    #[test]
+    fn matches_missing_label() {
+        let selector = Selector::new(None)
+            .with_matcher(LabelMatcher::new("env", MatchOp::Equal("".into())))
+            .with_matcher(LabelMatcher::new(
+                "__name__",
+                MatchOp::regex("up|down").unwrap(),
+            ));
+
+        assert!(selector.matches(&Sample::new("up", 1.0)));
+        assert!(!selector.matches(&Sample::new("up", 1.0).add_label("env", "prod")));
+        assert!(!selector.matches(&Sample::new("sideways", 1.0)));
+    }
+}
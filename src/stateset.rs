@@ -0,0 +1,185 @@
+use crate::{Family, Labels, MetricType, ValidationError};
+use std::collections::HashMap;
+
+/// A state of a [`Stateset`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct State {
+    /// The state name
+    pub name: String,
+    /// `true` if the state is active
+    pub enabled: bool,
+}
+
+/// The states of one stateset series
+///
+/// Create these with [`Family::statesets()`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stateset<'a> {
+    /// Labels of the series without the stateset label
+    pub labels: Labels<'a>,
+    /// States in exposition order
+    pub states: Vec<State>,
+}
+
+impl<'a> Stateset<'a> {
+    /// Create a `Stateset` without states for the series with `labels`
+    pub fn new(labels: Labels<'a>) -> Self {
+        Self {
+            labels,
+            states: vec![],
+        }
+    }
+
+    /// The names of the active states
+    pub fn active(&self) -> impl Iterator<Item = &str> {
+        self.states
+            .iter()
+            .filter(|state| state.enabled)
+            .map(|state| state.name.as_str())
+    }
+
+    /// Returns whether the state `name` is active, or `None` if there is no such state
+    pub fn get(&self, name: &str) -> Option<bool> {
+        self.states
+            .iter()
+            .find(|state| state.name == name)
+            .map(|state| state.enabled)
+    }
+}
+
+impl<'a> Family<'a> {
+    /// Group the samples of a stateset family into [`Stateset`]s by labels
+    ///
+    /// The family must have the type stateset.  Each sample must have a label named after the
+    /// family holding the state name and a value of 0 or 1.
+    ///
+    /// ```
+    /// use nom_openmetrics::parser::openmetrics;
+    ///
+    /// let input = concat!(
+    ///     "# TYPE breaker stateset\n",
+    ///     "breaker{breaker=\"open\"} 0\n",
+    ///     "breaker{breaker=\"closed\"} 1\n",
+    ///     "# EOF\n",
+    /// );
+    ///
+    /// let (_, families) = openmetrics(input).unwrap();
+    ///
+    /// let statesets = families[0].statesets().unwrap();
+    ///
+    /// assert_eq!(vec!["closed"], statesets[0].active().collect::<Vec<_>>());
+    /// ```
+    pub fn statesets(&self) -> Result<Vec<Stateset<'a>>, ValidationError> {
+        self.expect_type(MetricType::Stateset)?;
+
+        let name = self.name().unwrap_or_default();
+
+        let mut statesets: Vec<Stateset<'a>> = vec![];
+        let mut index: HashMap<Labels<'a>, usize> = HashMap::new();
+
+        for sample in &self.samples {
+            let mut labels = sample.labels().clone();
+
+            let Some(state) = labels.remove(name) else {
+                return Err(ValidationError::MissingLabel {
+                    sample_line: sample.to_string(),
+                    label: name.into(),
+                });
+            };
+
+            let enabled = match sample.number() {
+                0.0 => false,
+                1.0 => true,
+                value => {
+                    return Err(ValidationError::InvalidValue {
+                        sample_line: sample.to_string(),
+                        value,
+                    })
+                }
+            };
+
+            let i = *index.entry(labels.clone()).or_insert_with(|| {
+                statesets.push(Stateset::new(labels));
+
+                statesets.len() - 1
+            });
+
+            statesets[i].states.push(State {
+                name: state,
+                enabled,
+            });
+        }
+
+        Ok(statesets)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parser::openmetrics, test::parse};
+
+    const INPUT: &str = "# TYPE feature stateset
+feature{env=\"prod\",feature=\"a\"} 1
+feature{env=\"prod\",feature=\"b\"} 0
+feature{env=\"prod\",feature=\"c\"} 1
+feature{env=\"dev\",feature=\"a\"} 0
+# EOF
+";
+
+    #[test]
+    fn statesets() {
+        let (_, families) = parse(openmetrics, INPUT);
+
+        let statesets = families[0].statesets().unwrap();
+
+        assert_eq!(2, statesets.len());
+        assert_eq!(Some("prod"), statesets[0].labels.get("env"));
+        assert_eq!(vec!["a", "c"], statesets[0].active().collect::<Vec<_>>());
+        assert_eq!(Some(false), statesets[0].get("b"));
+        assert_eq!(None, statesets[0].get("d"));
+        assert_eq!(0, statesets[1].active().count());
+    }
+
+    #[test]
+    fn statesets_wrong_type() {
+        let input = "# TYPE feature gauge\nfeature{feature=\"a\"} 1\n# EOF\n";
+        let (_, families) = parse(openmetrics, input);
+
+        assert_eq!(
+            Err(ValidationError::WrongType {
+                family: "feature".into(),
+                expected: "stateset".into(),
+            }),
+            families[0].statesets()
+        );
+    }
+
+    #[test]
+    fn statesets_missing_label() {
+        let input = "# TYPE feature stateset\nfeature{env=\"prod\"} 1\n# EOF\n";
+        let (_, families) = parse(openmetrics, input);
+
+        assert_eq!(
+            Err(ValidationError::MissingLabel {
+                sample_line: "feature{env=\"prod\"} 1".into(),
+                label: "feature".into(),
+            }),
+            families[0].statesets()
+        );
+    }
+
+    #[test]
+    fn statesets_invalid_value() {
+        let input = "# TYPE feature stateset\nfeature{feature=\"a\"} 2\n# EOF\n";
+        let (_, families) = parse(openmetrics, input);
+
+        assert_eq!(
+            Err(ValidationError::InvalidValue {
+                sample_line: "feature{feature=\"a\"} 2".into(),
+                value: 2.0,
+            }),
+            families[0].statesets()
+        );
+    }
+}
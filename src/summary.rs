@@ -0,0 +1,154 @@
+use crate::{Family, Labels};
+use std::collections::HashMap;
+
+/// A quantile of a [`Summary`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quantile {
+    /// The `quantile` label value
+    pub quantile: f64,
+    /// The observed value at `quantile`
+    pub value: f64,
+}
+
+/// The quantile, `_count`, `_sum`, and `_created` samples of one summary series
+///
+/// Create these with [`Family::summaries()`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Summary<'a> {
+    /// Labels of the series without `quantile`
+    pub labels: Labels<'a>,
+    /// Quantiles in exposition order
+    pub quantiles: Vec<Quantile>,
+    /// The `_count` value
+    pub count: Option<f64>,
+    /// The `_sum` value
+    pub sum: Option<f64>,
+    /// The `_created` or `st@` start time in seconds since the Unix epoch
+    pub created: Option<f64>,
+}
+
+impl<'a> Summary<'a> {
+    /// Create an empty `Summary` for the series with `labels`
+    pub fn new(labels: Labels<'a>) -> Self {
+        Self {
+            labels,
+            quantiles: vec![],
+            count: None,
+            sum: None,
+            created: None,
+        }
+    }
+}
+
+impl<'a> Family<'a> {
+    /// Group the samples of a summary family into [`Summary`]s by labels
+    ///
+    /// Samples are grouped by the family name with a `quantile` label and the `_count`, `_sum`,
+    /// and `_created` suffixes.  Samples with other names or without a `quantile` label are
+    /// ignored, as are quantiles with an invalid `quantile` label.  Without a `_created` sample the
+    /// OpenMetrics 2.0 start timestamp of any sample is used.
+    pub fn summaries(&self) -> Vec<Summary<'a>> {
+        let name = self.name().unwrap_or_default();
+
+        let mut summaries: Vec<Summary<'a>> = vec![];
+        let mut index: HashMap<Labels<'a>, usize> = HashMap::new();
+
+        for sample in &self.samples {
+            let Some(suffix) = sample.name().strip_prefix(name) else {
+                continue;
+            };
+
+            let mut labels = sample.labels().clone();
+            let quantile = labels.remove("quantile");
+
+            let known = match suffix {
+                "" => quantile.is_some(),
+                "_count" | "_sum" | "_created" => true,
+                _ => false,
+            };
+
+            if !known {
+                continue;
+            }
+
+            let i = *index.entry(labels.clone()).or_insert_with(|| {
+                summaries.push(Summary::new(labels));
+
+                summaries.len() - 1
+            });
+
+            let summary = &mut summaries[i];
+
+            if let Some(start_timestamp) = sample.start_timestamp() {
+                summary.created.get_or_insert(start_timestamp);
+            }
+
+            match suffix {
+                "" => {
+                    if let Some(quantile) = quantile.and_then(|q| q.parse().ok()) {
+                        summary.quantiles.push(Quantile {
+                            quantile,
+                            value: sample.number(),
+                        });
+                    }
+                }
+                "_count" => summary.count = Some(sample.number()),
+                "_sum" => summary.sum = Some(sample.number()),
+                "_created" => summary.created = Some(sample.number()),
+                _ => {}
+            }
+        }
+
+        summaries
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parser::openmetrics, test::parse};
+
+    #[test]
+    fn summaries() {
+        let input = "# TYPE rpc summary
+rpc{service=\"a\",quantile=\"0.5\"} 0.05
+rpc{service=\"a\",quantile=\"0.99\"} 0.8
+rpc_count{service=\"a\"} 100
+rpc_sum{service=\"a\"} 9.5
+rpc_created{service=\"a\"} 1700000000
+rpc_count{service=\"b\"} 0
+rpc_sum{service=\"b\"} 0
+rpc{service=\"c\"} 1
+rpc_max{service=\"d\"} 2
+# EOF
+";
+        let (_, families) = parse(openmetrics, input);
+
+        assert_eq!(
+            vec![
+                Summary {
+                    labels: Labels::new().with("service", "a"),
+                    quantiles: vec![
+                        Quantile {
+                            quantile: 0.5,
+                            value: 0.05
+                        },
+                        Quantile {
+                            quantile: 0.99,
+                            value: 0.8
+                        },
+                    ],
+                    count: Some(100.0),
+                    sum: Some(9.5),
+                    created: Some(1700000000.0),
+                },
+                Summary {
+                    count: Some(0.0),
+                    sum: Some(0.0),
+                    ..Summary::new(Labels::new().with("service", "b"))
+                },
+            ],
+            families[0].summaries()
+        );
+    }
+}
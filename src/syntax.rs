@@ -0,0 +1,220 @@
+use crate::{Family, MetricDescriptor, Sample};
+use std::{borrow::Cow, fmt};
+
+/// A lossless parse of an exposition
+///
+/// Every line of the input is kept along with its original text, including comments and blank
+/// lines, so an unchanged `SyntaxTree` displays as exactly the input it was parsed from.
+#[derive(Debug, Default, PartialEq)]
+pub struct SyntaxTree<'a> {
+    lines: Vec<Line<'a>>,
+}
+
+impl<'a> SyntaxTree<'a> {
+    /// Create a `SyntaxTree` from [`Line`]s
+    pub fn new(lines: Vec<Line<'a>>) -> Self {
+        Self { lines }
+    }
+
+    /// The [`Line`]s of the exposition
+    pub fn lines(&self) -> &[Line<'a>] {
+        &self.lines
+    }
+
+    /// The [`Line`]s of the exposition for editing
+    pub fn lines_mut(&mut self) -> &mut Vec<Line<'a>> {
+        &mut self.lines
+    }
+
+    /// Convert into [`Family`]s
+    ///
+    /// Comments, blank lines, and the EOF marker are discarded.  A descriptor following a sample
+    /// starts a new family, as when parsing with [`prometheus()`](crate::parser::prometheus).
+    pub fn into_families(self) -> Vec<Family<'a>> {
+        let mut families = vec![];
+        let mut descriptors = vec![];
+        let mut samples = vec![];
+
+        for line in self.lines {
+            match line.kind {
+                LineKind::Descriptor(descriptor) => {
+                    if !samples.is_empty() {
+                        families.push(Family::new(
+                            std::mem::take(&mut descriptors),
+                            std::mem::take(&mut samples),
+                        ));
+                    }
+
+                    descriptors.push(descriptor);
+                }
+                LineKind::Sample(sample) => samples.push(sample),
+                LineKind::Comment(_) | LineKind::Blank(_) | LineKind::Eof => (),
+            }
+        }
+
+        if !descriptors.is_empty() || !samples.is_empty() {
+            families.push(Family::new(descriptors, samples));
+        }
+
+        families
+    }
+}
+
+impl fmt::Display for SyntaxTree<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            f.write_str(line.text())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A line of an exposition with its original text
+#[derive(Debug, PartialEq)]
+pub struct Line<'a> {
+    text: Cow<'a, str>,
+    kind: LineKind<'a>,
+}
+
+impl<'a> Line<'a> {
+    /// Create a `Line` written in canonical form from its contents
+    pub fn new(kind: LineKind<'a>) -> Self {
+        let text = Cow::Owned(format!("{kind}\n"));
+
+        Self { text, kind }
+    }
+
+    /// Create a `Line` from its original text
+    pub(crate) fn parsed(text: &'a str, kind: LineKind<'a>) -> Self {
+        let text = Cow::Borrowed(text);
+
+        Self { text, kind }
+    }
+
+    /// The contents of the line
+    pub fn kind(&self) -> &LineKind<'a> {
+        &self.kind
+    }
+
+    /// Replace the contents of the line, rewriting its text in canonical form
+    pub fn replace(&mut self, kind: LineKind<'a>) {
+        *self = Self::new(kind);
+    }
+
+    /// The text of the line including any trailing newline
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns `true` if the line has not been changed since parsing
+    pub fn is_original(&self) -> bool {
+        matches!(self.text, Cow::Borrowed(_))
+    }
+}
+
+/// The contents of a [`Line`]
+#[derive(Debug, PartialEq)]
+pub enum LineKind<'a> {
+    /// A HELP, TYPE, or UNIT line
+    Descriptor(MetricDescriptor<'a>),
+    /// A metric sample
+    Sample(Sample<'a>),
+    /// Any other comment, with the text following `#`
+    Comment(&'a str),
+    /// A line of only whitespace, without the newline
+    Blank(&'a str),
+    /// The OpenMetrics `# EOF` marker
+    Eof,
+}
+
+impl fmt::Display for LineKind<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LineKind::Descriptor(descriptor) => write!(f, "{descriptor}"),
+            LineKind::Sample(sample) => write!(f, "{sample}"),
+            LineKind::Comment(comment) => write!(f, "#{comment}"),
+            LineKind::Blank(blank) => f.write_str(blank),
+            LineKind::Eof => f.write_str("# EOF"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parser::syntax_tree, test::parse, MetricType};
+
+    const INPUT: &str = "# A leading comment\n\
+                         # HELP up Is the job up\n\
+                         # TYPE up gauge\n\
+                         up{job=\"prometheus\"} 1\n\
+                         \n\
+                         #another comment\n\
+                         up{job=\"grafana\"} 0\n\
+                         # TYPE requests counter\n\
+                         requests 1027\n\
+                         # EOF\n";
+
+    #[test]
+    fn display_round_trip() {
+        let (_, tree) = parse(syntax_tree, INPUT);
+
+        assert_eq!(INPUT, tree.to_string());
+        assert!(tree.lines().iter().all(Line::is_original));
+    }
+
+    #[test]
+    fn into_families() {
+        let (_, tree) = parse(syntax_tree, INPUT);
+
+        let families = tree.into_families();
+
+        assert_eq!(2, families.len());
+
+        assert_eq!(
+            Family::new(
+                vec![
+                    MetricDescriptor::help("up", "Is the job up".into()),
+                    MetricDescriptor::r#type("up", MetricType::Gauge),
+                ],
+                vec![
+                    Sample::new("up", 1.0).add_label("job", "prometheus"),
+                    Sample::new("up", 0.0).add_label("job", "grafana"),
+                ]
+            ),
+            families[0]
+        );
+
+        assert_eq!(
+            Family::new(
+                vec![MetricDescriptor::r#type("requests", MetricType::Counter)],
+                vec![Sample::new("requests", 1027.0)]
+            ),
+            families[1]
+        );
+    }
+
+    #[test]
+    fn replace() {
+        let (_, mut tree) = parse(syntax_tree, INPUT);
+
+        for line in tree.lines_mut() {
+            if let LineKind::Sample(sample) = line.kind() {
+                if sample.name() == "up" {
+                    let sample = Sample::new("up", sample.number()).add_label("job", "node");
+
+                    line.replace(LineKind::Sample(sample));
+                }
+            }
+        }
+
+        let expected = INPUT
+            .replace("job=\"prometheus\"", "job=\"node\"")
+            .replace("job=\"grafana\"", "job=\"node\"");
+
+        assert_eq!(expected, tree.to_string());
+        assert!(!tree.lines()[3].is_original());
+        assert!(tree.lines()[4].is_original());
+    }
+}
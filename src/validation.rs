@@ -0,0 +1,117 @@
+use crate::{Family, MetricType};
+use std::fmt;
+
+/// An error from a typed view of a [`Family`](crate::Family) whose samples do not match its type
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationError {
+    /// A sample is missing a label required by the type
+    MissingLabel {
+        /// The sample as written in an exposition
+        sample_line: String,
+        /// The missing label
+        label: String,
+    },
+    /// A histogram has a bucket count smaller than a bucket with a lower upper bound
+    NonMonotonicBuckets {
+        /// The series name and labels
+        series: String,
+    },
+    /// A histogram has no `+Inf` bucket
+    MissingInfBucket {
+        /// The series name and labels
+        series: String,
+    },
+    /// A `_bucket` sample has a missing or invalid `le` label
+    InvalidBucketBound {
+        /// The sample as written in an exposition
+        sample_line: String,
+    },
+    /// A histogram has no count
+    MissingCount {
+        /// The series name and labels
+        series: String,
+    },
+    /// A histogram count differs from its `+Inf` bucket
+    CountMismatch {
+        /// The series name and labels
+        series: String,
+        /// The count
+        count: f64,
+        /// The `+Inf` bucket count
+        bucket: f64,
+    },
+    /// A sample value is not allowed by the type
+    InvalidValue {
+        /// The sample as written in an exposition
+        sample_line: String,
+        /// The invalid value
+        value: f64,
+    },
+    /// A family does not have the type of the view
+    WrongType {
+        /// The family name
+        family: String,
+        /// The type of the view, such as `info`
+        expected: String,
+    },
+    /// A sample has the same series labels as an earlier sample
+    DuplicateSeries {
+        /// The sample as written in an exposition
+        sample_line: String,
+    },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingLabel { sample_line, label } => {
+                write!(f, "sample {sample_line} is missing label {label}")
+            }
+            Self::NonMonotonicBuckets { series } => {
+                write!(f, "histogram {series} bucket counts decrease")
+            }
+            Self::MissingInfBucket { series } => {
+                write!(f, "histogram {series} is missing the +Inf bucket")
+            }
+            Self::InvalidBucketBound { sample_line } => {
+                write!(f, "bucket {sample_line} has a missing or invalid le label")
+            }
+            Self::MissingCount { series } => {
+                write!(f, "histogram {series} is missing its count")
+            }
+            Self::CountMismatch {
+                series,
+                count,
+                bucket,
+            } => write!(
+                f,
+                "histogram {series} count {count} does not match +Inf bucket {bucket}"
+            ),
+            Self::InvalidValue { sample_line, value } => {
+                write!(f, "sample {sample_line} has invalid value {value}")
+            }
+            Self::WrongType { family, expected } => {
+                write!(f, "family {family} does not have type {expected}")
+            }
+            Self::DuplicateSeries { sample_line } => {
+                write!(f, "sample {sample_line} duplicates an earlier series")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl Family<'_> {
+    /// Check that this family has the TYPE `expected`
+    pub(crate) fn expect_type(&self, expected: MetricType<'_>) -> Result<(), ValidationError> {
+        if self.metric_type() == Some(&expected) {
+            return Ok(());
+        }
+
+        Err(ValidationError::WrongType {
+            family: self.name().unwrap_or_default().into(),
+            expected: expected.as_str().into(),
+        })
+    }
+}
@@ -0,0 +1,39 @@
+use crate::{Label, MetricDescriptor, Sample};
+
+/// Receives the contents of an exposition as it is parsed
+///
+/// Use a `Visitor` with [`visit_prometheus()`](crate::parser::visit_prometheus) or
+/// [`visit_openmetrics()`](crate::parser::visit_openmetrics) to consume an exposition without
+/// building a [`Family`](crate::Family) for each set of samples.
+///
+/// Methods are called in exposition order.  If parsing fails the visitor may already have
+/// received part of the exposition, but `on_eof()` will not be called.
+pub trait Visitor<'a> {
+    /// Called for each HELP, TYPE, or UNIT descriptor
+    fn on_descriptor(&mut self, _descriptor: MetricDescriptor<'a>) {}
+
+    /// Called with the metric name of each sample before the rest of the sample is parsed
+    ///
+    /// Return `false` to skip the sample.  A skipped sample is not passed to `on_sample()` and the
+    /// rest of its line is not parsed or validated.  This is not called for samples with a quoted
+    /// name, as in OpenMetrics 2.0.
+    fn wants_sample(&mut self, _name: &'a str) -> bool {
+        true
+    }
+
+    /// Called for each sample
+    fn on_sample(&mut self, _sample: Sample<'a>) {}
+
+    /// Called after the last sample of each family
+    fn on_family_end(&mut self) {}
+
+    /// Called after the entire exposition has been parsed
+    fn on_eof(&mut self) {}
+
+    /// Provide an empty buffer for the labels of the next sample
+    ///
+    /// Override this to reuse the label allocations of samples you have finished with.
+    fn labels_buffer(&mut self) -> Vec<Label<'a>> {
+        Vec::new()
+    }
+}